@@ -1,21 +1,95 @@
 use anyhow::Result;
 use chrono::{DateTime, Local};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use std::collections::VecDeque;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use sysinfo::{System, SystemExt};
+use crate::cluster::{self, ClusterHost, ClusterMetric};
+use crate::energy::EnergyTracker;
 use crate::export::CsvExporter;
-use crate::gpu::{GpuInfo, GpuManager};
-use crate::health::{HealthMonitor, GpuHealthMetrics, HealthStatus};
+use crate::filter::ProcessFilter;
+use crate::gpu::{ComputeMode, GpuInfo, GpuManager, GpuSelector};
+use crate::health::{HealthAlert, HealthMonitor, GpuHealthMetrics};
 use crate::notifications::{NotificationManager, NotificationQueue};
-use crate::process::{GpuProcess, ProcessManager};
-use crate::settings::{SettingsManager, AppSettings};
+use crate::priority::{self, IoClass, IoPriority};
+use crate::process::{GpuProcess, ProcessManager, ProcessSignal};
+use crate::process_tree::{self, ProcessRow};
+use crate::remote::RemoteHost;
+use crate::sampler::{HostStatus, Sampler, Snapshot, Source};
+use crate::ui;
+use crate::settings::{self, SettingsManager, AppSettings, SettingsField};
 
 #[derive(Debug, Clone)]
 pub struct HistoryPoint {
     pub timestamp: DateTime<Local>,
-    pub utilization: f32,
-    pub memory_usage: f32,
-    pub temperature: f32,
+    pub utilization: Option<f32>,
+    pub memory_usage: Option<f32>,
+    pub temperature: Option<f32>,
+}
+
+/// Remote hosts only report data; changes have to be made on the node itself
+fn remote_read_only(host: &str) -> String {
+    format!("🌐 {} is watched remotely and is read-only here; run gputop on it to make changes", host)
+}
+
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// How long a SIGTERM'd process gets to exit before SIGKILL
+const TERM_ESCALATION_TIMEOUT: Duration = Duration::from_secs(5);
+// How often signalled processes are polled while waiting for them to exit
+const ESCALATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Process and signal chosen in the confirmation dialog
+#[derive(Debug, Clone)]
+pub struct SignalRequest {
+    pub pid: u32,
+    pub start_time: u64,
+    pub command: String,
+    pub user: String,
+    pub memory_usage: u64,
+    pub device: Option<String>,
+    pub signal: usize,  // Index into ProcessSignal::ALL
+    pub escalate: bool, // Follow SIGTERM with SIGKILL after the timeout
+}
+
+/// GPU setting change chosen in the Hardware view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceControl {
+    ComputeMode(ComputeMode),
+    RaiseClocks,
+    LowerClocks,
+    ResetClocks,
+}
+
+impl DeviceControl {
+    pub fn description(&self) -> String {
+        match self {
+            DeviceControl::ComputeMode(mode) => format!("Set compute mode to {}", mode.text()),
+            DeviceControl::RaiseClocks => "Raise application clocks one step".to_string(),
+            DeviceControl::LowerClocks => "Lower application clocks one step".to_string(),
+            DeviceControl::ResetClocks => "Reset application clocks to defaults".to_string(),
+        }
+    }
+}
+
+/// Device and control waiting in the confirmation dialog
+#[derive(Debug, Clone)]
+pub struct DeviceControlRequest {
+    pub gpu_index: usize,
+    pub gpu_name: String,
+    pub current: String,  // Current value of the setting being changed
+    pub control: DeviceControl,
+}
+
+#[derive(Debug, Clone)]
+struct Escalation {
+    pid: u32,
+    start_time: u64,
+    command: String,
+    deadline: Instant,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,6 +98,7 @@ pub enum ViewMode {
     Performance,
     Hardware,
     Health,
+    Cluster,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -33,6 +108,7 @@ pub enum SortColumn {
     GpuUsage,
     MemoryUsage,
     Command,
+    Host,
 }
 
 
@@ -44,29 +120,57 @@ pub struct App {
     pub sort_ascending: bool,
     pub selected_process: Option<usize>,
     pub filter_text: String,
+    pub process_filter: Option<ProcessFilter>,
+    pub filter_error: Option<String>,
+    pub search_mode: bool,
     pub show_command_palette: bool,
     pub show_help: bool,
     pub show_process_details: bool,
     pub show_settings: bool,
-    pub update_interval: u64,
+    pub signal_dialog: Option<SignalRequest>,
+    pub device_control_dialog: Option<DeviceControlRequest>,
+    pub settings_selected: usize,
+    pub settings_edit: Option<String>,   // Edit buffer of the selected field
+    pub settings_error: Option<String>,
+    pub settings_saved: AppSettings,     // Last saved settings, restored on discard
+    applied_settings: AppSettings,       // Settings last pushed into the running components
+    pub update_interval: u64,          // Milliseconds, retuned at runtime with +/-
+    pub history_capacity: usize,
+    pub remote: bool,                  // Watching other hosts (--connect, --ssh) instead of this machine
+    pub hostname: String,              // This machine, for the cluster view
     pub debug_mode: bool,
     
     // Data
-    pub gpu_manager: GpuManager,
-    pub process_manager: ProcessManager,
-    pub health_monitor: HealthMonitor,
+    pub gpu_manager: Arc<GpuManager>,     // Shared with the sampler for device controls
+    pub process_manager: ProcessManager,  // Signals and priority changes only; the sampler has its own
+    pub sampler: Sampler,
+    pub last_sample: Arc<Snapshot>,
+    pub health_monitors: HashMap<String, HealthMonitor>,  // Local GPUs, by display id
+    pub gpu_health: HashMap<String, GpuHealthMetrics>,    // Latest health of every GPU on every host
+    pub energy_tracker: EnergyTracker,
     pub notification_manager: NotificationManager,
     pub notification_queue: NotificationQueue,
     pub settings_manager: SettingsManager,
     pub gpus: Vec<GpuInfo>,
     pub processes: Vec<GpuProcess>,
+    pub launchers: Vec<GpuProcess>,  // Non-GPU parents shown as tree roots
     pub history: VecDeque<HistoryPoint>,
     pub health_metrics: Option<GpuHealthMetrics>,
     
     // UI State
     pub panes_collapsed: bool,
     pub tree_view: bool,
+    pub collapsed_pids: HashSet<u32>,
+    pub group_by_container: bool,
     pub scroll_offset: usize,
+    pub process_table_offset: Cell<usize>,  // First visible table line, kept by the renderer
+    pub help_scroll: u16,
+    pub cluster_metric: ClusterMetric,
+    pub cluster_selected: usize,            // Host row in the cluster view
+    pub cluster_offset: Cell<usize>,        // First visible host row, kept by the renderer
+    pub focused_host: Option<String>,       // Host the other views are narrowed to from the cluster view
+    last_click: Option<(Instant, usize)>,    // For double-click detection
+    pending_escalations: Vec<Escalation>,
     pub command_palette_input: String,
     
     // Status message display
@@ -76,16 +180,44 @@ pub struct App {
 }
 
 impl App {
-    pub async fn new(update_interval: u64, selected_gpu: Option<usize>, debug: bool) -> Result<Self> {
-        let gpu_manager = GpuManager::new().await?;
-        let process_manager = ProcessManager::new();
-        let settings_manager = SettingsManager::new()?;
-        let gpus = gpu_manager.get_gpu_info().await?;
-        
-        let current_gpu = selected_gpu.unwrap_or(0);
-        if current_gpu >= gpus.len() {
-            anyhow::bail!("GPU index {} not found. Available GPUs: {}", current_gpu, gpus.len());
+    pub async fn new(
+        settings_manager: SettingsManager,
+        interval: Option<u64>,
+        history: Option<usize>,
+        selected_gpu: Option<GpuSelector>,
+        remote_hosts: Vec<RemoteHost>,
+        allow_mock: bool,
+        debug: bool,
+    ) -> Result<Self> {
+        let settings = settings_manager.get_settings();
+        let update_interval = settings::resolve_update_interval(interval, settings)?;
+        let history_capacity = settings::resolve_override(history, settings::HISTORY_ENV, settings.max_history_points)?;
+        if !(settings::MIN_HISTORY_POINTS..=settings::MAX_HISTORY_POINTS).contains(&history_capacity) {
+            anyhow::bail!(
+                "History size {} is out of range ({}-{})",
+                history_capacity, settings::MIN_HISTORY_POINTS, settings::MAX_HISTORY_POINTS
+            );
         }
+        
+        // Remote views don't show this machine's GPUs, so mock data is harmless
+        let remote = !remote_hosts.is_empty();
+        let gpu_manager = Arc::new(GpuManager::new(allow_mock || remote).await?);
+        let source = if remote {
+            Source::Remote { hosts: remote_hosts }
+        } else {
+            Source::Local { gpu_manager: gpu_manager.clone(), process_manager: Box::new(ProcessManager::new()) }
+        };
+        let sampler = Sampler::start(source)?;
+        let last_sample = sampler.latest();
+        let gpus = last_sample.gpus.clone();
+        
+        let current_gpu = match &selected_gpu {
+            Some(selector) => selector.resolve(&gpus).ok_or_else(|| {
+                let available: Vec<String> = gpus.iter().map(|g| g.display_id()).collect();
+                anyhow::anyhow!("GPU {:?} not found. Available GPUs: {}", selector, available.join(", "))
+            })?,
+            None => 0,
+        };
 
         Ok(Self {
             should_quit: false,
@@ -95,27 +227,55 @@ impl App {
             sort_ascending: false,
             selected_process: None,
             filter_text: String::new(),
+            process_filter: None,
+            filter_error: None,
+            search_mode: false,
             show_command_palette: false,
             show_help: false,
             show_process_details: false,
             show_settings: false,
+            signal_dialog: None,
+            device_control_dialog: None,
+            settings_selected: 0,
+            settings_edit: None,
+            settings_error: None,
+            settings_saved: settings_manager.get_settings().clone(),
+            applied_settings: settings_manager.get_settings().clone(),
             update_interval,
+            history_capacity,
+            remote,
+            hostname: System::new().host_name().unwrap_or_else(|| "localhost".to_string()),
             debug_mode: debug,
             
             gpu_manager,
-            process_manager,
-            health_monitor: HealthMonitor::new(),
-            notification_manager: NotificationManager::new(),
-            notification_queue: NotificationQueue::new(),
+            process_manager: ProcessManager::new(),
+            sampler,
+            last_sample,
+            health_monitors: HashMap::new(),
+            gpu_health: HashMap::new(),
+            energy_tracker: EnergyTracker::new(),
+            notification_manager: NotificationManager::new(settings.notification_settings.clone()),
+            notification_queue: NotificationQueue::new(settings.notification_settings.min_interval_seconds),
             settings_manager,
             gpus,
             processes: Vec::new(),
-            history: VecDeque::with_capacity(300), // 5 minutes at 1Hz
+            launchers: Vec::new(),
+            history: VecDeque::with_capacity(history_capacity),
             health_metrics: None,
             
             panes_collapsed: false,
             tree_view: false,
+            collapsed_pids: HashSet::new(),
+            group_by_container: false,
             scroll_offset: 0,
+            process_table_offset: Cell::new(0),
+            help_scroll: 0,
+            cluster_metric: ClusterMetric::Utilization,
+            cluster_selected: 0,
+            cluster_offset: Cell::new(0),
+            focused_host: None,
+            last_click: None,
+            pending_escalations: Vec::new(),
             command_palette_input: String::new(),
             
             status_message: None,
@@ -124,50 +284,219 @@ impl App {
         })
     }
 
+    /// Periodic work on each timer tick. Sampling itself happens in the
+    /// background; its result arrives through `apply_sample`.
     pub async fn update(&mut self) -> Result<()> {
-        // Update GPU information
-        self.gpus = self.gpu_manager.get_gpu_info().await?;
+        self.sampler.request();
+        
+        self.reload_settings_if_changed();
+
+        // Check if status message should be cleared
+        self.update_status_message();
+
+        Ok(())
+    }
+
+    /// Takes over the sampler's latest snapshot
+    pub fn apply_sample(&mut self) {
+        let sample = self.sampler.latest();
+        if Arc::ptr_eq(&sample, &self.last_sample) {
+            return;
+        }
+        self.last_sample = sample.clone();
         
-        // Update processes
-        self.processes = self.process_manager.get_gpu_processes().await?;
+        if let Some(error) = &sample.error {
+            self.show_status_message(format!("⚠️ Sampling failed, showing previous data: {}", error));
+            return;
+        }
         
-        // Sort processes
-        self.sort_processes();
+        if !sample.warnings.is_empty() {
+            self.show_status_message(format!("⚠️ {}", sample.warnings.join(" • ")));
+        }
+        
+        self.update_gpu_health(&sample);
+        self.show_sample();
+        
+        // Attribute each GPU's energy to the processes running on it. PIDs
+        // aren't unique across agents, so this is local only. MIG instances
+        // report no power of their own; their processes belong to the parent.
+        if !self.remote {
+            for gpu in self.gpus.iter().filter(|gpu| gpu.mig.is_none()) {
+                let processes: Vec<&GpuProcess> = self.processes.iter()
+                    .filter(|p| p.gpu_index == Some(gpu.index))
+                    .collect();
+                self.energy_tracker.record(gpu.index, gpu.power_draw, &processes);
+            }
+        }
         
-        // Add to history and update health metrics
+        // Add to history
         if let Some(gpu) = self.gpus.get(self.current_gpu) {
             let history_point = HistoryPoint {
-                timestamp: Local::now(),
+                timestamp: sample.taken_at,
                 utilization: gpu.utilization,
-                memory_usage: (gpu.memory_used as f32 / gpu.memory_total as f32) * 100.0,
+                memory_usage: gpu.memory_percent().map(|pct| pct as f32),
                 temperature: gpu.temperature,
             };
             
             self.history.push_back(history_point);
-            if self.history.len() > 300 {
+            while self.history.len() > self.history_capacity {
                 self.history.pop_front();
             }
 
-            // Update health monitoring
-            self.health_metrics = Some(self.health_monitor.update_metrics(
-                gpu.temperature,
-                gpu.power_draw,
-                gpu.memory_used,
-                gpu.memory_total,
-                gpu.utilization,
-                gpu.gpu_clock,
-                gpu.memory_clock,
-                gpu.throttled,
-            ));
-
             // Health notifications disabled temporarily to avoid PowerShell issues
             // TODO: Re-enable when PowerShell notification issues are resolved
         }
+    }
 
-        // Check if status message should be cleared
-        self.update_status_message();
+    /// Health of every GPU in `sample`. Agents keep health history for each
+    /// of their GPUs; every other GPU gets its own monitor here.
+    fn update_gpu_health(&mut self, sample: &Snapshot) {
+        let thresholds = &self.settings_manager.get_settings().health_thresholds;
+        let monitors = &mut self.health_monitors;
+        let previous = std::mem::take(&mut self.gpu_health);
+        // Repeating a stale host's last data would flatten its trends
+        let stale: HashSet<&str> = sample.hosts.iter()
+            .filter(|host| host.is_stale())
+            .map(|host| host.name.as_str())
+            .collect();
+        self.gpu_health = sample.gpus.iter()
+            .map(|gpu| {
+                let id = gpu.display_id();
+                let is_stale = gpu.host.as_deref().is_some_and(|host| stale.contains(host));
+                let health = match (sample.health.get(&id), previous.get(&id)) {
+                    (Some(health), _) => health.clone(),
+                    (None, Some(health)) if is_stale => health.clone(),
+                    (None, _) => monitors.entry(id.clone())
+                        .or_insert_with(|| HealthMonitor::new(thresholds.clone()))
+                        .update_metrics(gpu),
+                };
+                (id, health)
+            })
+            .collect();
+    }
 
-        Ok(())
+    /// Loads the last sample's GPUs and processes, narrowed to the focused
+    /// host if there is one
+    fn show_sample(&mut self) {
+        let sample = self.last_sample.clone();
+        let on_focused_host = |host: &Option<String>| {
+            self.focused_host.is_none() || *host == self.focused_host
+        };
+        
+        // Stay on the same device when agents come and go
+        let current_id = self.gpus.get(self.current_gpu).map(|gpu| gpu.display_id());
+        let gpus: Vec<GpuInfo> = sample.gpus.iter().filter(|gpu| on_focused_host(&gpu.host)).cloned().collect();
+        let processes: Vec<GpuProcess> = sample.processes.iter().filter(|p| on_focused_host(&p.host)).cloned().collect();
+        let launchers: Vec<GpuProcess> = sample.launchers.iter().filter(|p| on_focused_host(&p.host)).cloned().collect();
+        
+        self.gpus = gpus;
+        self.processes = processes;
+        self.launchers = launchers;
+        self.current_gpu = current_id
+            .and_then(|id| self.gpus.iter().position(|gpu| gpu.display_id() == id))
+            .unwrap_or(0);
+        self.health_metrics = self.gpus.get(self.current_gpu)
+            .and_then(|gpu| self.gpu_health.get(&gpu.display_id()))
+            .cloned();
+        
+        // Sort processes
+        self.sort_processes();
+        
+        // Adjust selection if processes went away
+        let count = self.visible_processes().len();
+        if self.selected_process.unwrap_or(0) >= count && count > 0 {
+            self.selected_process = Some(count - 1);
+        }
+    }
+
+    /// Hosts of the last sample for the cluster view, unhealthiest first
+    pub fn cluster_hosts(&self) -> Vec<ClusterHost> {
+        cluster::cluster_hosts(&self.last_sample.gpus, &self.gpu_health)
+    }
+
+    /// Connection state of a remote host, `None` for this machine
+    pub fn host_status(&self, host: &Option<String>) -> Option<&HostStatus> {
+        let host = host.as_deref()?;
+        self.last_sample.hosts.iter().find(|status| status.name == host)
+    }
+
+    pub fn host_label<'a>(&'a self, host: &'a Option<String>) -> &'a str {
+        host.as_deref().unwrap_or(&self.hostname)
+    }
+
+    /// Recent alerts of the current GPU. Remote GPUs only report the
+    /// alerts of their latest health check.
+    pub fn recent_alerts(&self, limit: usize) -> Vec<HealthAlert> {
+        let Some(gpu) = self.gpus.get(self.current_gpu) else { return Vec::new() };
+        match self.health_monitors.get(&gpu.display_id()) {
+            Some(monitor) => monitor.get_recent_alerts(limit),
+            None => self.health_metrics.as_ref()
+                .map(|health| health.alerts.iter().rev().take(limit).cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Keys only the cluster view uses. Returns false for keys it leaves
+    /// to the common handling.
+    fn handle_cluster_key(&mut self, key: KeyEvent) -> bool {
+        let count = self.cluster_hosts().len();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.cluster_selected = self.cluster_selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.cluster_selected = (self.cluster_selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Home => self.cluster_selected = 0,
+            KeyCode::End => self.cluster_selected = count.saturating_sub(1),
+            KeyCode::Enter => self.focus_selected_host(),
+            KeyCode::Char('m') => self.cluster_metric = self.cluster_metric.next(),
+            KeyCode::Esc if self.focused_host.is_some() => self.set_focused_host(None),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Drill down: narrow the other views to the selected host and show its processes
+    fn focus_selected_host(&mut self) {
+        let Some(row) = self.cluster_hosts().into_iter().nth(self.cluster_selected) else { return };
+        // Local GPUs have no host, and there is only one local machine
+        if row.host.is_some() {
+            let label = self.host_label(&row.host).to_string();
+            self.set_focused_host(row.host);
+            self.show_status_message(format!("🔍 Showing {} only • Esc in the Cluster view shows all hosts", label));
+        }
+        if let Some(&first) = row.gpus.first() {
+            let id = self.last_sample.gpus[first].display_id();
+            self.current_gpu = self.gpus.iter().position(|gpu| gpu.display_id() == id).unwrap_or(0);
+        }
+        self.selected_process = None;
+        self.view_mode = ViewMode::Processes;
+    }
+
+    fn set_focused_host(&mut self, host: Option<String>) {
+        if self.focused_host != host {
+            self.focused_host = host;
+            self.show_sample();
+        }
+    }
+
+    /// Picks up external edits to the config files. Skipped while the
+    /// settings panel is open so a reload can't clobber the form.
+    fn reload_settings_if_changed(&mut self) {
+        if self.show_settings {
+            return;
+        }
+        
+        match self.settings_manager.reload_if_changed() {
+            Some(Ok(())) => {
+                self.settings_saved = self.settings_manager.get_settings().clone();
+                self.apply_settings();
+                self.show_status_message("🔄 Settings reloaded from config file".to_string());
+            }
+            Some(Err(e)) => {
+                self.show_status_message(format!("⚠️ Settings reload failed, keeping current settings: {:#}", e));
+            }
+            None => {}
+        }
     }
 
     pub async fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
@@ -176,6 +505,21 @@ impl App {
             return Ok(());
         }
 
+        if self.signal_dialog.is_some() {
+            self.handle_signal_dialog_key(key).await?;
+            return Ok(());
+        }
+
+        if self.device_control_dialog.is_some() {
+            self.handle_device_control_dialog_key(key).await?;
+            return Ok(());
+        }
+
+        if self.search_mode {
+            self.handle_search_key(key);
+            return Ok(());
+        }
+
 
         if self.show_help {
             self.handle_help_key(key).await?;
@@ -192,6 +536,10 @@ impl App {
             return Ok(());
         }
 
+        if self.view_mode == ViewMode::Cluster && self.handle_cluster_key(key) {
+            return Ok(());
+        }
+
         match key.code {
             // Navigation
             KeyCode::Up => self.select_previous(),
@@ -208,6 +556,8 @@ impl App {
             KeyCode::F(2) => self.switch_gpu(1),
             KeyCode::F(3) => self.switch_gpu(2),
             KeyCode::F(4) => self.switch_gpu(3),
+            KeyCode::Char(']') => self.cycle_device(true),
+            KeyCode::Char('[') => self.cycle_device(false),
             
             // View modes
             KeyCode::Tab => self.next_view_mode(),
@@ -215,23 +565,26 @@ impl App {
             
             // Actions
             KeyCode::Enter => self.show_process_details(),
-            KeyCode::Delete => {
-                if let Err(e) = self.kill_selected_process().await {
-                    // Don't crash the app on kill errors, just log them
-                    eprintln!("Error killing process: {}", e);
-                }
-            },
-            KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::ALT) => {
-                if let Err(e) = self.kill_selected_process().await {
-                    eprintln!("Error killing process: {}", e);
-                }
-            },
+            KeyCode::Delete => self.open_signal_dialog(),
+            KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::ALT) => self.open_signal_dialog(),
             
             // UI toggles
-            KeyCode::Char('t') => self.tree_view = !self.tree_view,
+            KeyCode::Char('t') => self.toggle_tree_view(),
+            KeyCode::Left if self.tree_view => self.set_selected_collapsed(Some(true)),
+            KeyCode::Right if self.tree_view => self.set_selected_collapsed(Some(false)),
+            KeyCode::Char(' ') if self.tree_view => self.set_selected_collapsed(None),
+            KeyCode::Char('g') => self.toggle_container_grouping(),
             KeyCode::Char('c') => self.panes_collapsed = !self.panes_collapsed,
+            KeyCode::Char('+') | KeyCode::Char('=') => self.adjust_update_interval(false),
+            KeyCode::Char('-') => self.adjust_update_interval(true),
             KeyCode::Char('h') => self.show_help = !self.show_help,
-            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::ALT) => self.show_settings = !self.show_settings,
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::ALT) => self.open_settings(),
+            
+            // Search
+            KeyCode::Char('/') => {
+                self.search_mode = true;
+            },
+            KeyCode::Esc if !self.filter_text.is_empty() => self.set_filter(String::new()),
             
             // Command palette
             KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -239,12 +592,17 @@ impl App {
                 self.command_palette_input.clear();
             },
             
+            // Device controls
+            KeyCode::Char('m') | KeyCode::Char('<') | KeyCode::Char('>') | KeyCode::Char('x')
+                if self.view_mode == ViewMode::Hardware => self.handle_device_control_key(key).await?,
+            
             // Sorting
             KeyCode::Char('1') => self.set_sort_column(SortColumn::Pid),
             KeyCode::Char('2') => self.set_sort_column(SortColumn::User),
             KeyCode::Char('3') => self.set_sort_column(SortColumn::GpuUsage),
             KeyCode::Char('4') => self.set_sort_column(SortColumn::MemoryUsage),
             KeyCode::Char('5') => self.set_sort_column(SortColumn::Command),
+            KeyCode::Char('6') if self.remote => self.set_sort_column(SortColumn::Host),
             
             // Export functionality
             KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -255,6 +613,9 @@ impl App {
             KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.export_processes_csv();
             },
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.export_energy_report(false);
+            },
             
             _ => {}
         }
@@ -285,48 +646,485 @@ impl App {
     }
 
 
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.search_mode = false;
+                self.set_filter(String::new());
+            },
+            KeyCode::Enter => {
+                self.search_mode = false;
+            },
+            KeyCode::Backspace => {
+                let mut text = self.filter_text.clone();
+                text.pop();
+                self.set_filter(text);
+            },
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.set_filter(String::new());
+            },
+            KeyCode::Char(c) => {
+                let mut text = self.filter_text.clone();
+                text.push(c);
+                self.set_filter(text);
+            },
+            _ => {}
+        }
+    }
+
+    /// Re-parses the filter on every edit so the table updates live. A term
+    /// that doesn't parse yet (e.g. a half-typed regex) keeps the last valid
+    /// filter and reports the error in the footer.
+    fn set_filter(&mut self, text: String) {
+        self.filter_text = text;
+        
+        match ProcessFilter::parse(&self.filter_text) {
+            Ok(filter) => {
+                self.process_filter = (!filter.is_empty()).then_some(filter);
+                self.filter_error = None;
+            }
+            Err(e) => self.filter_error = Some(e),
+        }
+        
+        let count = self.visible_processes().len();
+        self.selected_process = match self.selected_process {
+            _ if count == 0 => None,
+            Some(i) if i >= count => Some(count - 1),
+            selected => selected,
+        };
+    }
+
     async fn handle_help_key(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Char('h') | KeyCode::Esc => {
                 self.show_help = false;
+                self.help_scroll = 0;
             },
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_help(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_help(1),
+            KeyCode::PageUp => self.scroll_help(-10),
+            KeyCode::PageDown => self.scroll_help(10),
             _ => {}
         }
         Ok(())
     }
 
+    fn scroll_help(&mut self, lines: i32) {
+        let max = ui::help_line_count().saturating_sub(1) as i32;
+        self.help_scroll = (self.help_scroll as i32 + lines).clamp(0, max) as u16;
+    }
+
+    /// Mouse input. `area` is the terminal size, used to find what was hit.
+    /// Only the help modal takes mouse input while a dialog is open.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, area: Rect) {
+        if self.show_help {
+            match mouse.kind {
+                MouseEventKind::ScrollUp => self.scroll_help(-3),
+                MouseEventKind::ScrollDown => self.scroll_help(3),
+                _ => {}
+            }
+            return;
+        }
+        if self.show_command_palette || self.show_process_details || self.show_settings
+            || self.signal_dialog.is_some() || self.device_control_dialog.is_some() || self.search_mode
+        {
+            return;
+        }
+        
+        let layout = ui::screen_layout(area, self);
+        let in_table = self.view_mode == ViewMode::Processes
+            && mouse.row >= layout.main.y && mouse.row < layout.main.y + layout.main.height;
+        
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(mode) = ui::tab_at(layout.tabs, mouse.column, mouse.row) {
+                    self.view_mode = mode;
+                    self.last_click = None;
+                    return;
+                }
+                if self.view_mode == ViewMode::Cluster {
+                    self.click_cluster_row(ui::cluster_row_at(self, layout.main, mouse.column, mouse.row));
+                    return;
+                }
+                if !in_table {
+                    return;
+                }
+                
+                match ui::process_table_hit(self, layout.main, mouse.column, mouse.row) {
+                    Some(ui::TableHit::Sort(column)) => self.set_sort_column(column),
+                    Some(ui::TableHit::Process(index)) => {
+                        let now = Instant::now();
+                        let double_click = matches!(self.last_click,
+                            Some((at, last)) if last == index && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL);
+                        
+                        self.selected_process = Some(index);
+                        if double_click {
+                            self.show_process_details();
+                            self.last_click = None;
+                        } else {
+                            self.last_click = Some((now, index));
+                        }
+                    }
+                    None => {}
+                }
+            }
+            MouseEventKind::ScrollUp if in_table => {
+                for _ in 0..3 {
+                    self.select_previous_clamped();
+                }
+            }
+            MouseEventKind::ScrollDown if in_table => {
+                for _ in 0..3 {
+                    self.select_next_clamped();
+                }
+            }
+            MouseEventKind::ScrollUp if self.view_mode == ViewMode::Cluster => {
+                self.cluster_selected = self.cluster_selected.saturating_sub(1);
+            }
+            MouseEventKind::ScrollDown if self.view_mode == ViewMode::Cluster => {
+                let last = self.cluster_hosts().len().saturating_sub(1);
+                self.cluster_selected = (self.cluster_selected + 1).min(last);
+            }
+            _ => {}
+        }
+    }
+
+    /// Click selects a host, double-click drills down to it
+    fn click_cluster_row(&mut self, row: Option<usize>) {
+        let Some(row) = row else { return };
+        let now = Instant::now();
+        let double_click = matches!(self.last_click,
+            Some((at, last)) if last == row && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL);
+        
+        self.cluster_selected = row;
+        if double_click {
+            self.focus_selected_host();
+            self.last_click = None;
+        } else {
+            self.last_click = Some((now, row));
+        }
+    }
+
+    /// Wheel scrolling stops at the ends instead of wrapping around
+    fn select_previous_clamped(&mut self) {
+        if let Some(i) = self.selected_process.filter(|&i| i > 0) {
+            self.selected_process = Some(i - 1);
+        } else if self.selected_process.is_none() {
+            self.select_first();
+        }
+    }
+
+    fn select_next_clamped(&mut self) {
+        let count = self.visible_processes().len();
+        match self.selected_process {
+            Some(i) if i + 1 < count => self.selected_process = Some(i + 1),
+            None => self.select_first(),
+            _ => {}
+        }
+    }
+
     async fn handle_process_details_key(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Esc | KeyCode::Enter => {
                 self.show_process_details = false;
             },
+            // Lower nice means higher priority
+            KeyCode::Char('+') | KeyCode::Char('=') => self.adjust_selected_priority(|nice, io| (nice - 1, io)).await?,
+            KeyCode::Char('-') => self.adjust_selected_priority(|nice, io| (nice + 1, io)).await?,
+            KeyCode::Char('i') => self.adjust_selected_priority(|nice, io| (nice, io.next_class())).await?,
+            KeyCode::Char('<') => self.adjust_selected_priority(|nice, io| {
+                (nice, IoPriority { level: (io.level + 1).min(priority::IO_LEVEL_MAX), ..io })
+            }).await?,
+            KeyCode::Char('>') => self.adjust_selected_priority(|nice, io| {
+                (nice, IoPriority { level: io.level.saturating_sub(1), ..io })
+            }).await?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Applies a nice / I/O priority change to the selected process. Only
+    /// the values that actually change are written.
+    async fn adjust_selected_priority<F>(&mut self, change: F) -> Result<()>
+    where
+        F: FnOnce(i32, IoPriority) -> (i32, IoPriority),
+    {
+        let Some(process) = self.selected_process() else { return Ok(()) };
+        if let Some(host) = &process.host {
+            let message = remote_read_only(host);
+            self.show_status_message(message);
+            return Ok(());
+        }
+        let (pid, start_time, command) = (process.pid, process.start_time, process.command.clone());
+        let io = process.io_priority.unwrap_or(IoPriority { class: IoClass::BestEffort, level: 4 });
+        let (nice, new_io) = change(process.priority, io);
+        let nice = nice.clamp(priority::NICE_MIN, priority::NICE_MAX);
+        
+        let result = if nice != process.priority {
+            self.process_manager.renice(pid, start_time, nice)
+                .map(|()| format!("🎚️ {} (PID {}) nice set to {}", command, pid, nice))
+        } else if new_io != io {
+            self.process_manager.set_io_priority(pid, start_time, new_io)
+                .map(|()| format!("💽 {} (PID {}) I/O priority set to {}", command, pid, new_io.text()))
+        } else {
+            return Ok(());
+        };
+        
+        match result {
+            Ok(message) => {
+                self.show_status_message(message);
+                self.sampler.request();  // Show the new priority right away
+            }
+            Err(e) => self.show_status_message(format!("❌ {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Compute mode and application clock controls of the Hardware view.
+    /// They act on the physical GPU, also when a MIG instance is selected,
+    /// and only after confirmation.
+    async fn handle_device_control_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(gpu) = self.gpus.get(self.current_gpu) else { return Ok(()) };
+        if let Some(host) = &gpu.host {
+            if matches!(key.code, KeyCode::Char('m' | 'x' | '<' | '>')) {
+                let message = remote_read_only(host);
+                self.show_status_message(message);
+            }
+            return Ok(());
+        }
+        
+        let clocks = match (gpu.app_gpu_clock, gpu.app_memory_clock) {
+            (Some(graphics), Some(memory)) => format!("{}/{} MHz", graphics, memory),
+            _ => "Unknown".to_string(),
+        };
+        let mode = gpu.compute_mode.unwrap_or(ComputeMode::Default);
+        let (control, current) = match key.code {
+            KeyCode::Char('m') => (DeviceControl::ComputeMode(mode.next()), mode.text().to_string()),
+            KeyCode::Char('>') => (DeviceControl::RaiseClocks, clocks),
+            KeyCode::Char('<') => (DeviceControl::LowerClocks, clocks),
+            KeyCode::Char('x') => (DeviceControl::ResetClocks, clocks),
+            _ => return Ok(()),
+        };
+        
+        self.device_control_dialog = Some(DeviceControlRequest {
+            gpu_index: gpu.index,
+            gpu_name: gpu.name.clone(),
+            current,
+            control,
+        });
+        Ok(())
+    }
+
+    async fn handle_device_control_dialog_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('n') => self.device_control_dialog = None,
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if let Some(request) = self.device_control_dialog.take() {
+                    self.apply_device_control(request);
+                }
+            }
             _ => {}
         }
         Ok(())
     }
 
+    fn apply_device_control(&mut self, request: DeviceControlRequest) {
+        let index = request.gpu_index;
+        let result = match request.control {
+            DeviceControl::ComputeMode(mode) => {
+                self.gpu_manager.set_compute_mode(index, mode)
+                    .map(|()| format!("🔒 GPU {} compute mode set to {}", index, mode.text()))
+            }
+            DeviceControl::RaiseClocks | DeviceControl::LowerClocks => {
+                self.gpu_manager.step_application_clocks(index, request.control == DeviceControl::RaiseClocks)
+                    .map(|(graphics, memory)| format!("⏲️ GPU {} application clocks set to {}/{} MHz", index, graphics, memory))
+            }
+            DeviceControl::ResetClocks => {
+                self.gpu_manager.reset_application_clocks(index)
+                    .map(|()| format!("⏲️ GPU {} application clocks reset to defaults", index))
+            }
+        };
+        
+        match result {
+            Ok(message) => {
+                self.show_status_message(message);
+                self.sampler.request();
+            }
+            Err(e) => self.show_status_message(format!("❌ {}", e)),
+        }
+    }
+
     async fn handle_settings_key(&mut self, key: KeyEvent) -> Result<()> {
+        let field = SettingsField::ALL[self.settings_selected];
+        
+        // Typing into a field
+        if let Some(buffer) = &mut self.settings_edit {
+            match key.code {
+                KeyCode::Esc => {
+                    self.settings_edit = None;
+                    self.settings_error = None;
+                },
+                KeyCode::Enter => {
+                    let input = buffer.clone();
+                    match field.set(self.settings_manager.get_settings_mut(), &input) {
+                        Ok(()) => {
+                            self.settings_edit = None;
+                            self.settings_error = None;
+                            self.apply_settings();
+                        }
+                        Err(e) => self.settings_error = Some(e),
+                    }
+                },
+                KeyCode::Backspace => {
+                    buffer.pop();
+                },
+                KeyCode::Char(c) => buffer.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+        
         match key.code {
-            KeyCode::Esc => {
-                self.show_settings = false;
+            KeyCode::Esc => self.close_settings(),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.settings_selected = self.settings_selected
+                    .checked_sub(1)
+                    .unwrap_or(SettingsField::ALL.len() - 1);
+                self.settings_error = None;
+            },
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.settings_selected = (self.settings_selected + 1) % SettingsField::ALL.len();
+                self.settings_error = None;
+            },
+            KeyCode::Enter | KeyCode::Char(' ') if field.is_toggle() => {
+                let settings = self.settings_manager.get_settings_mut();
+                let toggled = if field.value(settings) == "Yes" { "no" } else { "yes" };
+                if let Err(e) = field.set(settings, toggled) {
+                    self.settings_error = Some(e);
+                }
+                self.apply_settings();
+            },
+            KeyCode::Enter => {
+                self.settings_edit = Some(field.value(self.settings_manager.get_settings()));
             },
             KeyCode::Char('r') => {
                 // Reset to defaults
-                self.settings_manager.reset_to_defaults()?;
+                match self.settings_manager.reset_to_defaults() {
+                    Ok(()) => {
+                        self.settings_saved = self.settings_manager.get_settings().clone();
+                        self.apply_settings();
+                        self.show_status_message("↩️ Settings reset to defaults".to_string());
+                    }
+                    Err(e) => self.settings_error = Some(format!("Reset failed: {}", e)),
+                }
             },
             KeyCode::Char('s') => {
                 // Save settings
-                self.settings_manager.save_settings()?;
-                self.notification_manager.send_export_success("Settings saved to config file");
+                if let Err(e) = self.settings_manager.get_settings().validate() {
+                    self.settings_error = Some(e);
+                    return Ok(());
+                }
+                match self.settings_manager.save_settings() {
+                    Ok(()) => {
+                        self.settings_saved = self.settings_manager.get_settings().clone();
+                        self.settings_error = None;
+                        self.notification_manager.send_export_success("Settings saved to config file");
+                        self.show_status_message(format!(
+                            "💾 Settings saved to {}",
+                            self.settings_manager.config_path().display()
+                        ));
+                    }
+                    Err(e) => self.settings_error = Some(format!("Save failed: {}", e)),
+                }
             },
             _ => {}
         }
         Ok(())
     }
 
+    fn open_settings(&mut self) {
+        self.show_settings = true;
+        self.settings_edit = None;
+        self.settings_error = None;
+        self.settings_saved = self.settings_manager.get_settings().clone();
+    }
+
+    /// Closing without saving discards the previewed changes
+    fn close_settings(&mut self) {
+        self.show_settings = false;
+        
+        if self.settings_dirty() {
+            *self.settings_manager.get_settings_mut() = self.settings_saved.clone();
+            self.apply_settings();
+            self.show_status_message("↩️ Unsaved settings changes discarded".to_string());
+        }
+    }
+
+    pub fn settings_dirty(&self) -> bool {
+        *self.settings_manager.get_settings() != self.settings_saved
+    }
+
+    /// Pushes the current settings into the running components so edits
+    /// preview live. Interval and history only follow the settings when
+    /// they changed, so command line and environment overrides survive
+    /// unrelated edits.
+    fn apply_settings(&mut self) {
+        let settings = self.settings_manager.get_settings().clone();
+        for monitor in self.health_monitors.values_mut() {
+            monitor.set_thresholds(settings.health_thresholds.clone());
+        }
+        self.notification_manager.set_settings(settings.notification_settings.clone());
+        self.notification_queue.set_min_interval(settings.notification_settings.min_interval_seconds);
+        
+        if settings.update_interval_ms != self.applied_settings.update_interval_ms {
+            self.update_interval = settings.update_interval_ms;
+        }
+        if settings.max_history_points != self.applied_settings.max_history_points {
+            self.history_capacity = settings.max_history_points;
+            self.trim_history();
+        }
+        
+        self.applied_settings = settings;
+    }
+
+    fn trim_history(&mut self) {
+        while self.history.len() > self.history_capacity {
+            self.history.pop_front();
+        }
+    }
+
+    /// Steps the update interval along a fixed ladder for this session only
+    fn adjust_update_interval(&mut self, faster: bool) {
+        const STEPS: [u64; 9] = [100, 250, 500, 1000, 2000, 5000, 10_000, 30_000, 60_000];
+        
+        let next = if faster {
+            STEPS.iter().rev().find(|&&ms| ms < self.update_interval)
+        } else {
+            STEPS.iter().find(|&&ms| ms > self.update_interval)
+        };
+        
+        match next {
+            Some(&ms) => {
+                self.update_interval = ms;
+                self.show_status_message(format!("⏱️ Update interval: {}ms", ms));
+            }
+            None => self.show_status_message(format!("⏱️ Update interval already at {}ms", self.update_interval)),
+        }
+    }
+
+    /// Whether keys go to a text input rather than the global shortcuts
+    pub fn is_text_input_active(&self) -> bool {
+        self.search_mode || self.show_command_palette || self.settings_edit.is_some()
+    }
+
     async fn execute_command(&mut self) -> Result<()> {
         let command = self.command_palette_input.trim().to_lowercase();
         
+        if let Some(filter) = self.command_palette_input.trim().strip_prefix("filter ") {
+            self.set_filter(filter.trim().to_string());
+            return Ok(());
+        }
+        
         match command.as_str() {
             "quit" | "q" => self.should_quit = true,
             "sort pid" => self.set_sort_column(SortColumn::Pid),
@@ -334,33 +1132,100 @@ impl App {
             "sort gpu" => self.set_sort_column(SortColumn::GpuUsage),
             "sort memory" => self.set_sort_column(SortColumn::MemoryUsage),
             "sort command" => self.set_sort_column(SortColumn::Command),
-            "tree" => self.tree_view = !self.tree_view,
+            "sort host" => self.set_sort_column(SortColumn::Host),
+            "tree" => self.toggle_tree_view(),
+            "search" => self.search_mode = true,
+            "clear filter" | "filter" => self.set_filter(String::new()),
+            "group" | "group container" => self.toggle_container_grouping(),
             "collapse" => self.panes_collapsed = !self.panes_collapsed,
             "help" => self.show_help = !self.show_help,
+            "settings" => self.open_settings(),
+            "energy" | "energy csv" => self.export_energy_report(false),
+            "energy json" => self.export_energy_report(true),
             _ => {}
         }
         
         Ok(())
     }
 
+    /// Rows of the process table. A MIG instance only lists the processes
+    /// attached to it; whole GPUs list everything. The search filter applies
+    /// to GPU processes only. In tree view, launchers
+    /// of the listed processes are added as parents.
+    pub fn visible_rows(&self) -> Vec<ProcessRow<'_>> {
+        let mut processes: Vec<&GpuProcess> = match self.gpus.get(self.current_gpu) {
+            Some(gpu) if gpu.mig.is_some() => self.processes.iter()
+                .filter(|p| gpu.runs_process(p))
+                .collect(),
+            _ => self.processes.iter().collect(),
+        };
+        
+        if let Some(filter) = &self.process_filter {
+            processes.retain(|p| filter.matches(p, &self.gpus));
+        }
+        
+        if self.tree_view {
+            let parents: HashSet<(Option<&str>, u32)> = processes.iter()
+                .filter_map(|p| p.parent_pid.map(|parent| (p.host.as_deref(), parent)))
+                .collect();
+            processes.extend(self.launchers.iter().filter(|l| parents.contains(&(l.host.as_deref(), l.pid))));
+        }
+        
+        // Containers first, host processes last; keeps the sort order within a group
+        if self.group_by_container {
+            processes.sort_by_key(|p| (p.container.is_none(), p.container_label()));
+        }
+        
+        if self.tree_view {
+            // PIDs are only unique per host, so each agent gets its own tree
+            let mut hosts: Vec<Option<&str>> = Vec::new();
+            for process in &processes {
+                if !hosts.contains(&process.host.as_deref()) {
+                    hosts.push(process.host.as_deref());
+                }
+            }
+            hosts.into_iter()
+                .flat_map(|host| {
+                    let on_host: Vec<&GpuProcess> = processes.iter()
+                        .copied()
+                        .filter(|p| p.host.as_deref() == host)
+                        .collect();
+                    process_tree::build(&on_host, &self.collapsed_pids)
+                })
+                .collect()
+        } else {
+            processes.into_iter().map(ProcessRow::flat).collect()
+        }
+    }
+
+    pub fn visible_processes(&self) -> Vec<&GpuProcess> {
+        self.visible_rows().into_iter().map(|row| row.process).collect()
+    }
+
+    pub fn selected_process(&self) -> Option<&GpuProcess> {
+        self.selected_process.and_then(|i| self.visible_processes().get(i).copied())
+    }
+
     fn select_previous(&mut self) {
-        if self.processes.is_empty() {
+        let count = self.visible_processes().len();
+        if count == 0 {
             return;
         }
         
         match self.selected_process {
             Some(i) if i > 0 => self.selected_process = Some(i - 1),
-            _ => self.selected_process = Some(self.processes.len() - 1),
+            _ => self.selected_process = Some(count - 1),
         }
     }
 
     fn select_next(&mut self) {
-        if self.processes.is_empty() {
+        let count = self.visible_processes().len();
+        if count == 0 {
             return;
         }
         
         match self.selected_process {
-            Some(i) if i < self.processes.len() - 1 => self.selected_process = Some(i + 1),
+            Some(i) if i < count - 1 => self.selected_process = Some(i + 1),
             _ => self.selected_process = Some(0),
         }
     }
@@ -378,72 +1243,211 @@ impl App {
     }
 
     fn select_first(&mut self) {
-        if !self.processes.is_empty() {
+        if !self.visible_processes().is_empty() {
             self.selected_process = Some(0);
         }
     }
 
     fn select_last(&mut self) {
-        if !self.processes.is_empty() {
-            self.selected_process = Some(self.processes.len() - 1);
+        let count = self.visible_processes().len();
+        if count > 0 {
+            self.selected_process = Some(count - 1);
         }
     }
 
     fn switch_gpu(&mut self, gpu_idx: usize) {
-        if gpu_idx < self.gpus.len() {
-            self.current_gpu = gpu_idx;
+        if let Some(position) = GpuSelector::Index(gpu_idx).resolve(&self.gpus) {
+            self.current_gpu = position;
             self.selected_process = None;
         }
     }
 
+    fn toggle_tree_view(&mut self) {
+        self.tree_view = !self.tree_view;
+        self.selected_process = None;
+    }
+
+    /// Collapses (`Some(true)`), expands (`Some(false)`) or toggles the selected subtree
+    fn set_selected_collapsed(&mut self, collapsed: Option<bool>) {
+        let Some(row) = self.selected_process.and_then(|i| self.visible_rows().into_iter().nth(i)) else {
+            return;
+        };
+        if !row.has_children {
+            return;
+        }
+        
+        let pid = row.process.pid;
+        let collapse = collapsed.unwrap_or(!row.collapsed);
+        if collapse {
+            self.collapsed_pids.insert(pid);
+        } else {
+            self.collapsed_pids.remove(&pid);
+        }
+    }
+
+    fn toggle_container_grouping(&mut self) {
+        self.group_by_container = !self.group_by_container;
+        self.selected_process = None;
+    }
+
+    /// Steps through every device, including MIG instances
+    fn cycle_device(&mut self, forward: bool) {
+        if self.gpus.is_empty() {
+            return;
+        }
+        
+        let count = self.gpus.len();
+        self.current_gpu = if forward {
+            (self.current_gpu + 1) % count
+        } else {
+            (self.current_gpu + count - 1) % count
+        };
+        self.selected_process = None;
+    }
+
     fn next_view_mode(&mut self) {
         self.view_mode = match self.view_mode {
             ViewMode::Processes => ViewMode::Performance,
             ViewMode::Performance => ViewMode::Hardware,
             ViewMode::Hardware => ViewMode::Health,
-            ViewMode::Health => ViewMode::Processes,
+            ViewMode::Health => ViewMode::Cluster,
+            ViewMode::Cluster => ViewMode::Processes,
         };
     }
 
     fn prev_view_mode(&mut self) {
         self.view_mode = match self.view_mode {
-            ViewMode::Processes => ViewMode::Health,
+            ViewMode::Processes => ViewMode::Cluster,
             ViewMode::Performance => ViewMode::Processes,
             ViewMode::Hardware => ViewMode::Performance,
             ViewMode::Health => ViewMode::Hardware,
+            ViewMode::Cluster => ViewMode::Health,
         };
     }
 
     fn show_process_details(&mut self) {
-        if self.selected_process.is_some() && !self.processes.is_empty() {
+        if self.selected_process().is_some() {
             self.show_process_details = true;
         }
     }
 
-    async fn kill_selected_process(&mut self) -> Result<()> {
-        if let Some(selected_idx) = self.selected_process {
-            if let Some(process) = self.processes.get(selected_idx) {
-                let pid = process.pid;
-                let process_name = process.command.clone();
+    /// Opens the signal confirmation dialog for the selected process
+    fn open_signal_dialog(&mut self) {
+        let Some(process) = self.selected_process() else { return };
+        if let Some(host) = &process.host {
+            let message = remote_read_only(host);
+            self.show_status_message(message);
+            return;
+        }
+        
+        let device = process.gpu_index
+            .and_then(|i| self.gpus.iter().find(|g| g.index == i && g.mig.is_none()))
+            .map(|g| format!("GPU {} ({})", g.index, g.name));
+        
+        self.signal_dialog = Some(SignalRequest {
+            pid: process.pid,
+            start_time: process.start_time,
+            command: process.command.clone(),
+            user: process.user.clone(),
+            memory_usage: process.memory_usage,
+            device,
+            signal: 0,
+            escalate: true,
+        });
+    }
+
+    async fn handle_signal_dialog_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(request) = &mut self.signal_dialog else { return Ok(()) };
+        let count = ProcessSignal::ALL.len();
+        
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('n') => self.signal_dialog = None,
+            KeyCode::Up | KeyCode::Char('k') => request.signal = (request.signal + count - 1) % count,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => request.signal = (request.signal + 1) % count,
+            KeyCode::Char(c @ '1'..='5') => request.signal = c as usize - '1' as usize,
+            KeyCode::Char('e') => request.escalate = !request.escalate,
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if let Some(request) = self.signal_dialog.take() {
+                    self.send_signal(request).await?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn send_signal(&mut self, request: SignalRequest) -> Result<()> {
+        let signal = ProcessSignal::ALL[request.signal];
+        
+        match self.process_manager.send_signal(request.pid, request.start_time, signal) {
+            Ok(()) => {
+                if signal == ProcessSignal::Term && request.escalate {
+                    self.show_status_message(format!(
+                        "📨 Sent SIGTERM to {} (PID {}), SIGKILL in {}s if it keeps running",
+                        request.command, request.pid, TERM_ESCALATION_TIMEOUT.as_secs()
+                    ));
+                    self.pending_escalations.push(Escalation {
+                        pid: request.pid,
+                        start_time: request.start_time,
+                        command: request.command.clone(),
+                        deadline: Instant::now() + TERM_ESCALATION_TIMEOUT,
+                    });
+                } else {
+                    self.show_status_message(format!(
+                        "📨 Sent {} to {} (PID {})", signal.name(), request.command, request.pid
+                    ));
+                }
                 
-                match self.process_manager.kill_process(pid) {
+                if signal == ProcessSignal::Kill {
+                    self.notification_manager.send_process_killed(&request.command, request.pid);
+                }
+                self.sampler.request();  // So signalled processes disappear right away
+            }
+            Err(e) => self.show_status_message(format!("❌ {}", e)),
+        }
+        Ok(())
+    }
+
+    /// When the event loop should next call `check_escalations`, independent
+    /// of the sampling interval. None while no SIGTERM is pending.
+    pub fn next_escalation_check(&self) -> Option<Instant> {
+        let earliest = self.pending_escalations.iter().map(|e| e.deadline).min()?;
+        Some(earliest.min(Instant::now() + ESCALATION_POLL_INTERVAL))
+    }
+
+    /// Follows up on SIGTERMs: forgets processes that exited and sends
+    /// SIGKILL to the ones still running past the timeout
+    pub async fn check_escalations(&mut self) -> Result<()> {
+        if self.pending_escalations.is_empty() {
+            return Ok(());
+        }
+        
+        let now = Instant::now();
+        let mut changed = false;
+        for escalation in std::mem::take(&mut self.pending_escalations) {
+            if !self.process_manager.is_running(escalation.pid, escalation.start_time) {
+                self.show_status_message(format!("✅ {} (PID {}) exited after SIGTERM", escalation.command, escalation.pid));
+                changed = true;
+            } else if now >= escalation.deadline {
+                match self.process_manager.send_signal(escalation.pid, escalation.start_time, ProcessSignal::Kill) {
                     Ok(()) => {
-                        eprintln!("Successfully killed process: {} (PID: {})", process_name, pid);
-                        // Refresh the process list immediately to show the change
-                        self.processes = self.process_manager.get_gpu_processes().await?;
-                        self.sort_processes();
-                        
-                        // Adjust selection if needed
-                        if self.selected_process.unwrap_or(0) >= self.processes.len() && !self.processes.is_empty() {
-                            self.selected_process = Some(self.processes.len() - 1);
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to kill process: {}", e);
+                        self.show_status_message(format!(
+                            "⚡ {} (PID {}) ignored SIGTERM for {}s, sent SIGKILL",
+                            escalation.command, escalation.pid, TERM_ESCALATION_TIMEOUT.as_secs()
+                        ));
+                        self.notification_manager.send_process_killed(&escalation.command, escalation.pid);
                     }
+                    Err(e) => self.show_status_message(format!("❌ {}", e)),
                 }
+                changed = true;
+            } else {
+                self.pending_escalations.push(escalation);
             }
         }
+        
+        if changed {
+            self.sampler.request();  // So signalled processes disappear right away
+        }
         Ok(())
     }
 
@@ -482,6 +1486,29 @@ impl App {
         }
     }
 
+    fn export_energy_report(&mut self, json: bool) {
+        let report = self.energy_tracker.report(&self.settings_manager.get_settings().energy_settings);
+        let extension = if json { "json" } else { "csv" };
+        let filename = CsvExporter::get_export_filename("gputop_energy", extension);
+        
+        let result = if json {
+            CsvExporter::export_energy_report_json(&report, &filename)
+        } else {
+            CsvExporter::export_energy_report_csv(&report, &filename)
+        };
+        
+        match result {
+            Ok(()) => {
+                self.show_status_message(format!(
+                    "✅ Energy report exported: {} ({:.3} kWh, {:.2} {})",
+                    filename, report.total.energy_kwh, report.total.cost, report.currency
+                ));
+            }
+            Err(e) => {
+                self.show_status_message(format!("❌ Energy report export failed: {}", e));
+            }
+        }
+    }
 
     fn set_sort_column(&mut self, column: SortColumn) {
         if self.sort_column == column {
@@ -501,6 +1528,7 @@ impl App {
                 SortColumn::GpuUsage => a.gpu_usage.partial_cmp(&b.gpu_usage).unwrap_or(std::cmp::Ordering::Equal),
                 SortColumn::MemoryUsage => a.memory_usage.cmp(&b.memory_usage),
                 SortColumn::Command => a.command.cmp(&b.command),
+                SortColumn::Host => a.host.cmp(&b.host).then(a.pid.cmp(&b.pid)),
             };
             
             if self.sort_ascending {
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use sysinfo::{System, SystemExt};
+use crate::gpu::DeviceProcess;
+use crate::process::ProcessManager;
+
+/// Processes reported as GPU users in the driver-backed run
+const GPU_PROCESSES: usize = 8;
+
+/// Spacing between ticks, short enough that a run spans a few discoveries
+const TICK_SPACING: Duration = Duration::from_millis(250);
+
+/// Idle child processes, killed when dropped
+struct Sleepers(Vec<Child>);
+
+impl Drop for Sleepers {
+    fn drop(&mut self) {
+        for child in &mut self.0 {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// `gputop bench`: starts `processes` idle processes and prints how long
+/// process collection takes per tick, against a full sysinfo refresh
+pub fn run(processes: usize, ticks: usize) -> Result<()> {
+    println!("Starting {} idle processes...", processes);
+    let sleepers = spawn_sleepers(processes)?;
+    let total = count_processes();
+    println!("{} processes running\n", total);
+
+    // What every tick used to cost
+    let mut system = System::new_all();
+    let legacy = time_ticks(ticks, || system.refresh_all());
+    report("System::refresh_all (previous behaviour)", &legacy);
+
+    // Driver reports a handful of GPU processes: targeted refresh between discoveries
+    let device_processes: Vec<DeviceProcess> = sleepers.0.iter()
+        .take(GPU_PROCESSES)
+        .map(|child| DeviceProcess {
+            pid: child.id(),
+            gpu_index: 0,
+            gpu_instance_id: None,
+            compute_instance_id: None,
+            memory_used: None,
+            gpu_usage: None,
+            encoder_usage: None,
+            decoder_usage: None,
+        })
+        .collect();
+    let mut manager = ProcessManager::new();
+    let targeted = time_ticks(ticks, || collect(&mut manager, &device_processes));
+    report(&format!("ProcessManager, {} driver-reported PIDs", device_processes.len()), &targeted);
+
+    // No driver: name heuristics, candidates refreshed between discoveries
+    let mut manager = ProcessManager::new();
+    let heuristic = time_ticks(ticks, || collect(&mut manager, &[]));
+    report("ProcessManager, heuristic detection", &heuristic);
+
+    Ok(())
+}
+
+fn spawn_sleepers(count: usize) -> Result<Sleepers> {
+    let mut sleepers = Sleepers(Vec::with_capacity(count));
+    for _ in 0..count {
+        let child = Command::new("sleep")
+            .arg("3600")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start idle process {} (check `ulimit -u`)", sleepers.0.len() + 1))?;
+        sleepers.0.push(child);
+    }
+    Ok(sleepers)
+}
+
+fn count_processes() -> usize {
+    std::fs::read_dir("/proc")
+        .map(|entries| {
+            entries.filter_map(|e| e.ok())
+                .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+fn collect(manager: &mut ProcessManager, device_processes: &[DeviceProcess]) {
+    if let Ok(processes) = futures::executor::block_on(manager.get_gpu_processes(device_processes)) {
+        manager.get_launchers(&processes);
+    }
+}
+
+fn time_ticks(ticks: usize, mut tick: impl FnMut()) -> Vec<Duration> {
+    (0..ticks)
+        .map(|_| {
+            let started = Instant::now();
+            tick();
+            let elapsed = started.elapsed();
+            std::thread::sleep(TICK_SPACING.saturating_sub(elapsed));
+            elapsed
+        })
+        .collect()
+}
+
+fn report(name: &str, times: &[Duration]) {
+    let Some(max) = times.iter().max() else { return };
+    let mut sorted = times.to_vec();
+    sorted.sort();
+    let median = sorted[sorted.len() / 2];
+    let mean = times.iter().sum::<Duration>() / times.len() as u32;
+
+    println!("{}", name);
+    println!("  ticks: {}  mean: {:.1}ms  median: {:.1}ms  max: {:.1}ms",
+        times.len(), ms(mean), ms(median), ms(*max));
+    let per_tick: Vec<String> = times.iter().map(|t| format!("{:.1}", ms(*t))).collect();
+    println!("  per tick (ms): {}\n", per_tick.join(" "));
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
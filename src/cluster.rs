@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use crate::gpu::GpuInfo;
+use crate::health::GpuHealthMetrics;
+use crate::settings::HealthThresholds;
+
+/// What the cluster heatmap colours its cells by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClusterMetric {
+    Utilization,
+    Temperature,
+    Health,
+}
+
+impl ClusterMetric {
+    pub fn next(self) -> Self {
+        match self {
+            ClusterMetric::Utilization => ClusterMetric::Temperature,
+            ClusterMetric::Temperature => ClusterMetric::Health,
+            ClusterMetric::Health => ClusterMetric::Utilization,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ClusterMetric::Utilization => "⚡ Utilization",
+            ClusterMetric::Temperature => "🌡️ Temperature",
+            ClusterMetric::Health => "🏥 Health Score",
+        }
+    }
+
+    pub fn value(self, gpu: &GpuInfo, health: Option<&GpuHealthMetrics>) -> Option<f32> {
+        match self {
+            ClusterMetric::Utilization => gpu.utilization,
+            ClusterMetric::Temperature => gpu.temperature,
+            ClusterMetric::Health => health.map(|h| h.overall_score),
+        }
+    }
+
+    /// The `GpuInfo` metric behind this one, for why it is missing
+    pub fn sensor(self) -> Option<&'static str> {
+        match self {
+            ClusterMetric::Utilization => Some("utilization"),
+            ClusterMetric::Temperature => Some("temperature"),
+            ClusterMetric::Health => None,
+        }
+    }
+
+    pub fn format(self, value: f32) -> String {
+        match self {
+            ClusterMetric::Utilization => format!("{:.0}%", value),
+            ClusterMetric::Temperature => format!("{:.0}°C", value),
+            ClusterMetric::Health => format!("{:.0}", value),
+        }
+    }
+
+    /// How alarming `value` is, from 0.0 (cool) to 1.0 (hot)
+    pub fn heat(self, value: f32, thresholds: &HealthThresholds) -> f32 {
+        let heat = match self {
+            ClusterMetric::Utilization => value / 100.0,
+            // Room temperature is cold, the critical threshold is hot
+            ClusterMetric::Temperature => (value - 30.0) / (thresholds.temperature_critical - 30.0).max(1.0),
+            // Scores at or below 50 are as bad as it gets
+            ClusterMetric::Health => (100.0 - value) / 50.0,
+        };
+        heat.clamp(0.0, 1.0)
+    }
+}
+
+/// One heatmap row: a host and its GPUs in index order
+#[derive(Debug, Clone)]
+pub struct ClusterHost {
+    pub host: Option<String>,     // None for this machine
+    pub gpus: Vec<usize>,         // Indices into the snapshot's GPUs
+    pub worst_score: Option<f32>, // Lowest health score among the GPUs
+}
+
+/// Groups `gpus` by host, unhealthiest host first. Hosts without any
+/// health data yet go last.
+pub fn cluster_hosts(gpus: &[GpuInfo], health: &HashMap<String, GpuHealthMetrics>) -> Vec<ClusterHost> {
+    let mut hosts: Vec<ClusterHost> = Vec::new();
+    for (index, gpu) in gpus.iter().enumerate() {
+        let score = health.get(&gpu.display_id()).map(|h| h.overall_score);
+        let host = match hosts.iter_mut().find(|h| h.host == gpu.host) {
+            Some(host) => host,
+            None => {
+                hosts.push(ClusterHost { host: gpu.host.clone(), gpus: Vec::new(), worst_score: None });
+                hosts.last_mut().expect("just pushed")
+            }
+        };
+        host.gpus.push(index);
+        if let Some(score) = score {
+            host.worst_score = Some(host.worst_score.map_or(score, |worst| worst.min(score)));
+        }
+    }
+
+    hosts.sort_by(|a, b| match (a.worst_score, b.worst_score) {
+        (Some(x), Some(y)) => x.total_cmp(&y).then_with(|| a.host.cmp(&b.host)),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.host.cmp(&b.host),
+    });
+    hosts
+}
@@ -0,0 +1,348 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContainerRuntime {
+    Docker,
+    Containerd,
+    Podman,
+    CriO,
+    Systemd,  // Not a container, but a service unit worth grouping by
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PodInfo {
+    pub uid: String,
+    pub name: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerInfo {
+    pub runtime: ContainerRuntime,
+    pub id: String,
+    pub pod: Option<PodInfo>,
+}
+
+impl ContainerRuntime {
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker
+            | ContainerRuntime::Containerd
+            | ContainerRuntime::Podman
+            | ContainerRuntime::CriO => "🐳",
+            ContainerRuntime::Systemd => "⚙️",
+        }
+    }
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Containerd => "containerd",
+            ContainerRuntime::Podman => "podman",
+            ContainerRuntime::CriO => "cri-o",
+            ContainerRuntime::Systemd => "systemd",
+        }
+    }
+}
+
+impl ContainerInfo {
+    /// Container IDs are shortened to 12 characters like `docker ps` does
+    pub fn short_id(&self) -> &str {
+        if self.runtime == ContainerRuntime::Systemd {
+            &self.id
+        } else {
+            &self.id[..self.id.len().min(12)]
+        }
+    }
+
+    /// Label used for grouping: `namespace/pod` for Kubernetes, otherwise the short ID
+    pub fn label(&self) -> String {
+        match &self.pod {
+            Some(PodInfo { name: Some(name), namespace, .. }) => {
+                format!("{}/{}", namespace.as_deref().unwrap_or("?"), name)
+            }
+            Some(pod) => format!("pod {}", &pod.uid[..pod.uid.len().min(8)]),
+            None => self.short_id().to_string(),
+        }
+    }
+}
+
+/// Attributes a process to a container from the contents of `/proc/<pid>/cgroup`.
+///
+/// Handles cgroup v1 and v2 paths written by the cgroupfs and systemd drivers:
+/// `/docker/<id>`, `docker-<id>.scope`, `libpod-<id>.scope`, `crio-<id>.scope`,
+/// `cri-containerd-<id>.scope`, `/kubepods/.../pod<uid>/<id>` and plain
+/// `system.slice/<unit>.service` units.
+pub fn parse_cgroup(content: &str) -> Option<ContainerInfo> {
+    let mut systemd_unit = None;
+    
+    for line in content.lines() {
+        // hierarchy-ID:controller-list:cgroup-path
+        let Some(path) = line.splitn(3, ':').nth(2) else {
+            continue;
+        };
+        
+        if let Some(container) = parse_cgroup_path(path) {
+            return Some(container);
+        }
+        
+        if systemd_unit.is_none() {
+            systemd_unit = parse_systemd_unit(path);
+        }
+    }
+    
+    systemd_unit.map(|unit| ContainerInfo {
+        runtime: ContainerRuntime::Systemd,
+        id: unit,
+        pod: None,
+    })
+}
+
+fn parse_cgroup_path(path: &str) -> Option<ContainerInfo> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    
+    let pod = components.iter()
+        .find_map(|c| parse_pod_uid(c))
+        .map(|uid| PodInfo { uid, name: None, namespace: None });
+    let in_kubepods = components.iter().any(|c| c.starts_with("kubepods"));
+    
+    // The container ID is the innermost component that carries one
+    for component in components.iter().rev() {
+        let component = component.trim_end_matches(".scope");
+        
+        let prefixed = [
+            ("docker-", ContainerRuntime::Docker),
+            ("libpod-", ContainerRuntime::Podman),
+            ("crio-", ContainerRuntime::CriO),
+            ("cri-containerd-", ContainerRuntime::Containerd),
+        ];
+        for (prefix, runtime) in prefixed {
+            if let Some(id) = component.strip_prefix(prefix) {
+                if is_container_id(id) {
+                    return Some(ContainerInfo { runtime, id: id.to_string(), pod });
+                }
+            }
+        }
+        
+        if is_container_id(component) {
+            let parent = path.trim_end_matches(component);
+            let runtime = if parent.contains("/docker") {
+                ContainerRuntime::Docker
+            } else if parent.contains("libpod") || parent.contains("machine.slice") {
+                ContainerRuntime::Podman
+            } else {
+                // cgroupfs-driven kubelet and plain containerd namespaces
+                ContainerRuntime::Containerd
+            };
+            return Some(ContainerInfo { runtime, id: component.to_string(), pod });
+        }
+    }
+    
+    // Pod sandbox without a recognisable container component
+    if in_kubepods {
+        if let Some(pod) = pod {
+            return Some(ContainerInfo {
+                runtime: ContainerRuntime::Containerd,
+                id: pod.uid.clone(),
+                pod: Some(pod),
+            });
+        }
+    }
+    
+    None
+}
+
+/// `pod<uid>` (cgroupfs) or `kubepods-burstable-pod<uid_with_underscores>.slice` (systemd)
+fn parse_pod_uid(component: &str) -> Option<String> {
+    let component = component.trim_end_matches(".slice");
+    let start = component.rfind("pod")?;
+    let uid = component[start + 3..].replace('_', "-");
+    
+    let valid = uid.len() == 36 && uid.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    valid.then_some(uid)
+}
+
+fn parse_systemd_unit(path: &str) -> Option<String> {
+    let path = path.strip_prefix("/system.slice/")?;
+    let unit = path.split('/').next()?;
+    unit.ends_with(".service").then(|| unit.to_string())
+}
+
+fn is_container_id(id: &str) -> bool {
+    id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Resolves pod names and namespaces from the kubelet's pod directory
+/// (`<root>/<uid>/etc-hosts` and the projected service account volume),
+/// falling back to the container's own `HOSTNAME` and service account.
+pub struct PodResolver {
+    kubelet_pods_dir: PathBuf,
+    cache: HashMap<String, (Option<String>, Option<String>)>,
+}
+
+impl PodResolver {
+    pub fn new(kubelet_pods_dir: PathBuf) -> Self {
+        Self {
+            kubelet_pods_dir,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn resolve(&mut self, pid: u32, pod: &mut PodInfo) {
+        if let Some((name, namespace)) = self.cache.get(&pod.uid) {
+            pod.name = name.clone();
+            pod.namespace = namespace.clone();
+            return;
+        }
+        
+        let pod_dir = self.kubelet_pods_dir.join(&pod.uid);
+        let name = fs::read_to_string(pod_dir.join("etc-hosts")).ok()
+            .and_then(|hosts| pod_name_from_hosts(&hosts))
+            .or_else(|| hostname_from_environ(pid));
+        let namespace = namespace_from_pod_dir(&pod_dir)
+            .or_else(|| {
+                fs::read_to_string(format!("/proc/{}/root/var/run/secrets/kubernetes.io/serviceaccount/namespace", pid)).ok()
+            })
+            .map(|ns| ns.trim().to_string());
+        
+        // Only cache complete answers; the kubelet may not have written everything yet
+        if name.is_some() && namespace.is_some() {
+            self.cache.insert(pod.uid.clone(), (name.clone(), namespace.clone()));
+        }
+        pod.name = name;
+        pod.namespace = namespace;
+    }
+}
+
+/// The kubelet-managed hosts file ends with `<ip> <hostname>...` for the pod itself
+fn pod_name_from_hosts(hosts: &str) -> Option<String> {
+    hosts.lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty() && !line.starts_with('#'))
+        .and_then(|line| line.split_whitespace().next_back())
+        .map(|name| name.to_string())
+}
+
+fn namespace_from_pod_dir(pod_dir: &std::path::Path) -> Option<String> {
+    let projected = pod_dir.join("volumes/kubernetes.io~projected");
+    fs::read_dir(projected).ok()?
+        .flatten()
+        .find_map(|entry| fs::read_to_string(entry.path().join("namespace")).ok())
+}
+
+fn hostname_from_environ(pid: u32) -> Option<String> {
+    let environ = fs::read(format!("/proc/{}/environ", pid)).ok()?;
+    environ.split(|b| *b == 0)
+        .filter_map(|var| std::str::from_utf8(var).ok())
+        .find_map(|var| var.strip_prefix("HOSTNAME="))
+        .map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCKER_V1: &str = include_str!("../tests/fixtures/cgroup/docker-v1.txt");
+    const DOCKER_SYSTEMD: &str = include_str!("../tests/fixtures/cgroup/docker-systemd-v2.txt");
+    const CONTAINERD: &str = include_str!("../tests/fixtures/cgroup/containerd-v2.txt");
+    const CONTAINERD_KUBEPODS: &str = include_str!("../tests/fixtures/cgroup/containerd-kubepods-systemd.txt");
+    const CRIO_KUBEPODS: &str = include_str!("../tests/fixtures/cgroup/crio-kubepods-systemd.txt");
+    const PODMAN: &str = include_str!("../tests/fixtures/cgroup/podman-rootless.txt");
+    const KUBEPODS_CGROUPFS: &str = include_str!("../tests/fixtures/cgroup/kubepods-cgroupfs-v1.txt");
+    const SYSTEMD_SERVICE: &str = include_str!("../tests/fixtures/cgroup/systemd-service.txt");
+    const USER_SESSION: &str = include_str!("../tests/fixtures/cgroup/user-session.txt");
+
+    fn container(cgroup: &str) -> ContainerInfo {
+        parse_cgroup(cgroup).expect("cgroup should be attributed")
+    }
+
+    #[test]
+    fn docker_cgroupfs_v1() {
+        let c = container(DOCKER_V1);
+        assert_eq!(c.runtime, ContainerRuntime::Docker);
+        assert_eq!(c.id, "3f4e8c2a9b1d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f");
+        assert_eq!(c.short_id(), "3f4e8c2a9b1d");
+        assert_eq!(c.pod, None);
+    }
+
+    #[test]
+    fn docker_systemd_scope() {
+        let c = container(DOCKER_SYSTEMD);
+        assert_eq!(c.runtime, ContainerRuntime::Docker);
+        assert_eq!(c.id, "a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90");
+    }
+
+    #[test]
+    fn containerd_namespace() {
+        let c = container(CONTAINERD);
+        assert_eq!(c.runtime, ContainerRuntime::Containerd);
+        assert_eq!(c.id, "9d8c7b6a5f4e3d2c1b0a99887766554433221100ffeeddccbbaa998877665544");
+        assert_eq!(c.pod, None);
+    }
+
+    #[test]
+    fn podman_rootless_scope() {
+        let c = container(PODMAN);
+        assert_eq!(c.runtime, ContainerRuntime::Podman);
+        assert_eq!(c.id, "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210");
+    }
+
+    #[test]
+    fn kubepods_systemd_driver() {
+        let c = container(CONTAINERD_KUBEPODS);
+        assert_eq!(c.runtime, ContainerRuntime::Containerd);
+        assert_eq!(c.id, "c0ffee00c0ffee00c0ffee00c0ffee00c0ffee00c0ffee00c0ffee00c0ffee00");
+        assert_eq!(c.pod.as_ref().map(|p| p.uid.as_str()), Some("0a1b2c3d-4e5f-4a6b-9c7d-8e9f0a1b2c3d"));
+
+        let c = container(CRIO_KUBEPODS);
+        assert_eq!(c.runtime, ContainerRuntime::CriO);
+        assert_eq!(c.id, "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef");
+        assert_eq!(c.pod.as_ref().map(|p| p.uid.as_str()), Some("5f2c8e1a-4b3d-4c9e-8a7f-1e2d3c4b5a69"));
+    }
+
+    #[test]
+    fn kubepods_cgroupfs_driver() {
+        let c = container(KUBEPODS_CGROUPFS);
+        assert_eq!(c.runtime, ContainerRuntime::Containerd);
+        assert_eq!(c.id, "5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f");
+        let pod = c.pod.as_ref().unwrap();
+        assert_eq!(pod.uid, "7c1d2e3f-4a5b-4c6d-8e7f-9a0b1c2d3e4f");
+        assert_eq!(c.label(), "pod 7c1d2e3f");
+    }
+
+    #[test]
+    fn pod_sandbox_without_container() {
+        let c = container("0::/kubepods/besteffort/pod7c1d2e3f-4a5b-4c6d-8e7f-9a0b1c2d3e4f");
+        assert_eq!(c.id, "7c1d2e3f-4a5b-4c6d-8e7f-9a0b1c2d3e4f");
+        assert!(c.pod.is_some());
+    }
+
+    #[test]
+    fn systemd_slices() {
+        let c = container(SYSTEMD_SERVICE);
+        assert_eq!(c.runtime, ContainerRuntime::Systemd);
+        assert_eq!(c.id, "ollama.service");
+        assert_eq!(c.label(), "ollama.service");
+
+        // Login sessions and the root cgroup aren't worth grouping by
+        assert_eq!(parse_cgroup(USER_SESSION), None);
+        assert_eq!(parse_cgroup("0::/"), None);
+        assert_eq!(parse_cgroup(""), None);
+    }
+
+    #[test]
+    fn pod_uids() {
+        assert_eq!(parse_pod_uid("pod7c1d2e3f-4a5b-4c6d-8e7f-9a0b1c2d3e4f").as_deref(), Some("7c1d2e3f-4a5b-4c6d-8e7f-9a0b1c2d3e4f"));
+        assert_eq!(
+            parse_pod_uid("kubepods-burstable-pod5f2c8e1a_4b3d_4c9e_8a7f_1e2d3c4b5a69.slice").as_deref(),
+            Some("5f2c8e1a-4b3d-4c9e-8a7f-1e2d3c4b5a69")
+        );
+        assert_eq!(parse_pod_uid("kubepods.slice"), None);
+        assert_eq!(parse_pod_uid("burstable"), None);
+        assert_eq!(parse_pod_uid("podman"), None);
+        assert_eq!(parse_pod_uid("pod7c1d2e3f-4a5b"), None);
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use std::path::Path;
+use crate::gpu::{self, BackendState, GpuManager, NVIDIA_SMI_BACKEND};
+use crate::nvidia_smi::NvidiaSmi;
+
+/// `gputop doctor`: what each GPU backend found, why the others can't be
+/// used and whether gputop would fall back to mock data. Exits with an
+/// error when no backend works.
+pub async fn run() -> Result<()> {
+    println!("🩺 gputop doctor\n");
+
+    let manager = GpuManager::new(true).await?;
+    println!("GPU backends (in order of preference):");
+    for backend in manager.backends() {
+        let mut detail = backend.state.detail().to_string();
+        // Skipped backends are still worth knowing about as a fallback
+        if backend.name == NVIDIA_SMI_BACKEND && matches!(backend.state, BackendState::Skipped(_)) {
+            match NvidiaSmi::probe() {
+                Ok((_, gpus)) => detail.push_str(&format!("; would also work: {}", gpu::describe_gpus(&gpus))),
+                Err(e) => detail.push_str(&format!("; wouldn't work either: {:#}", e)),
+            }
+        }
+        println!("  {} {:<12} {}", backend.state.emoji(), backend.name, detail);
+    }
+
+    println!("\nDevice files:");
+    let devices = nvidia_device_files();
+    if devices.is_empty() {
+        println!("  ❌ no /dev/nvidia* files (driver not loaded, or not passed into the container)");
+    } else {
+        println!("  {}", devices.join(" "));
+    }
+    if in_container() {
+        println!("  📦 Running in a container: GPUs must be passed in, e.g. `docker run --gpus all` with the NVIDIA Container Toolkit");
+    }
+
+    println!("\nGPUs:");
+    let gpus = manager.get_gpu_info().await?;
+    for gpu in &gpus {
+        println!(
+            "  [{}] {} • {} • driver {}{}",
+            gpu.display_id(),
+            gpu.name,
+            gpu.memory_total.map_or("memory unknown".to_string(), |total| format!("{:.1} GB", total as f64 / (1024.0 * 1024.0 * 1024.0))),
+            gpu.driver_version,
+            if gpu.mock { " • MOCK" } else { "" }
+        );
+    }
+
+    println!();
+    if manager.uses_mock_data() {
+        println!("⚠️  No GPU backend available: gputop would show a mock GPU with made-up numbers.");
+        println!("   Fix one of the backends above, or pass --no-mock to fail instead of showing mock data.");
+        anyhow::bail!("no GPU backend available");
+    }
+    println!("✅ gputop will show real data");
+    Ok(())
+}
+
+fn nvidia_device_files() -> Vec<String> {
+    let mut devices: Vec<String> = std::fs::read_dir("/dev")
+        .map(|entries| {
+            entries.filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .filter(|name| name.starts_with("nvidia"))
+                .map(|name| format!("/dev/{}", name))
+                .collect()
+        })
+        .unwrap_or_default();
+    devices.sort();
+    devices
+}
+
+fn in_container() -> bool {
+    Path::new("/.dockerenv").exists()
+        || Path::new("/run/.containerenv").exists()
+        || std::env::var_os("KUBERNETES_SERVICE_HOST").is_some()
+}
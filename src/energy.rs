@@ -0,0 +1,264 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Instant;
+use crate::process::GpuProcess;
+use crate::settings::EnergySettings;
+
+/// Energy attributed to a single process over the monitoring session
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessEnergy {
+    pub pid: u32,
+    pub user: String,
+    pub command: String,
+    pub container: Option<String>,
+    pub energy_wh: f64,
+}
+
+/// Aggregated energy, cost and emissions for one report row
+#[derive(Debug, Clone, Serialize)]
+pub struct EnergyUsage {
+    pub name: String,
+    pub processes: usize,
+    pub energy_kwh: f64,
+    pub cost: f64,
+    pub carbon_kg: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnergyReport {
+    pub generated_at: DateTime<Local>,
+    pub session_start: DateTime<Local>,
+    pub currency: String,
+    pub price_per_kwh: f64,
+    pub carbon_intensity_g_per_kwh: f64,
+    pub total: EnergyUsage,
+    pub unattributed: EnergyUsage,
+    pub by_user: Vec<EnergyUsage>,
+    pub by_container: Vec<EnergyUsage>,
+    pub by_process: Vec<EnergyUsage>,
+}
+
+/// Per-process energy attribution. Each tick a GPU's power draw is split
+/// between the processes on that GPU proportionally to their SM utilization
+/// as sampled by NVML, or to their GPU memory when there is no sample; energy
+/// drawn while no process is on the GPU is tracked as unattributed (idle).
+pub struct EnergyTracker {
+    session_start: DateTime<Local>,
+    last_sample: HashMap<usize, Instant>, // keyed by GPU index
+    per_process: HashMap<(u32, String), ProcessEnergy>, // keyed by pid and command to survive PID reuse
+    unattributed_wh: f64,
+}
+
+impl EnergyTracker {
+    pub fn new() -> Self {
+        Self {
+            session_start: Local::now(),
+            last_sample: HashMap::new(),
+            per_process: HashMap::new(),
+            unattributed_wh: 0.0,
+        }
+    }
+
+    /// Records one tick of GPU `gpu_index`; `processes` are the ones running on it
+    pub fn record(&mut self, gpu_index: usize, power_draw: Option<f32>, processes: &[&GpuProcess]) {
+        self.record_at(Instant::now(), gpu_index, power_draw, processes);
+    }
+
+    fn record_at(&mut self, now: Instant, gpu_index: usize, power_draw: Option<f32>, processes: &[&GpuProcess]) {
+        let elapsed = self.last_sample.insert(gpu_index, now).map(|last| now.duration_since(last));
+
+        let (Some(power), Some(elapsed)) = (power_draw, elapsed) else {
+            return;
+        };
+
+        let energy_wh = power as f64 * elapsed.as_secs_f64() / 3600.0;
+        // nvidia-smi, remote hosts and drivers without per-process samples
+        // report no SM usage, only memory
+        let mut weights: Vec<f64> = processes.iter().map(|p| p.gpu_usage.unwrap_or(0.0).max(0.0) as f64).collect();
+        if weights.iter().sum::<f64>() <= 0.0 {
+            weights = processes.iter().map(|p| p.memory_usage as f64).collect();
+        }
+        let total_weight: f64 = weights.iter().sum();
+        if total_weight <= 0.0 {
+            self.unattributed_wh += energy_wh;
+            return;
+        }
+
+        for (process, weight) in processes.iter().zip(weights) {
+            let share = weight / total_weight;
+            let entry = self.per_process
+                .entry((process.pid, process.command.clone()))
+                .or_insert_with(|| ProcessEnergy {
+                    pid: process.pid,
+                    user: process.user.clone(),
+                    command: process.command.clone(),
+                    container: process.container_label(),
+                    energy_wh: 0.0,
+                });
+            entry.energy_wh += energy_wh * share;
+        }
+    }
+
+    pub fn process_energy_wh(&self, pid: u32) -> Option<f64> {
+        self.per_process.values()
+            .filter(|p| p.pid == pid)
+            .map(|p| p.energy_wh)
+            .reduce(|a, b| a + b)
+    }
+
+    pub fn report(&self, settings: &EnergySettings) -> EnergyReport {
+        let usage = |name: String, processes: usize, energy_wh: f64| {
+            let energy_kwh = energy_wh / 1000.0;
+            EnergyUsage {
+                name,
+                processes,
+                energy_kwh,
+                cost: energy_kwh * settings.price_per_kwh,
+                carbon_kg: energy_kwh * settings.carbon_intensity_g_per_kwh / 1000.0,
+            }
+        };
+
+        let group_by = |key: &dyn Fn(&ProcessEnergy) -> String| {
+            let mut groups: HashMap<String, (usize, f64)> = HashMap::new();
+            for process in self.per_process.values() {
+                let group = groups.entry(key(process)).or_insert((0, 0.0));
+                group.0 += 1;
+                group.1 += process.energy_wh;
+            }
+            let mut rows: Vec<EnergyUsage> = groups.into_iter()
+                .map(|(name, (processes, energy_wh))| usage(name, processes, energy_wh))
+                .collect();
+            rows.sort_by(|a, b| b.energy_kwh.partial_cmp(&a.energy_kwh).unwrap_or(std::cmp::Ordering::Equal));
+            rows
+        };
+
+        let attributed_wh: f64 = self.per_process.values().map(|p| p.energy_wh).sum();
+
+        EnergyReport {
+            generated_at: Local::now(),
+            session_start: self.session_start,
+            currency: settings.currency.clone(),
+            price_per_kwh: settings.price_per_kwh,
+            carbon_intensity_g_per_kwh: settings.carbon_intensity_g_per_kwh,
+            total: usage("total".to_string(), self.per_process.len(), attributed_wh + self.unattributed_wh),
+            unattributed: usage("idle".to_string(), 0, self.unattributed_wh),
+            by_user: group_by(&|p| p.user.clone()),
+            by_container: group_by(&|p| p.container.clone().unwrap_or_else(|| "host".to_string())),
+            by_process: group_by(&|p| format!("{} ({})", p.command, p.pid)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::process::ProcessManager;
+
+    fn settings() -> EnergySettings {
+        EnergySettings { price_per_kwh: 0.5, carbon_intensity_g_per_kwh: 400.0, currency: "EUR".to_string() }
+    }
+
+    /// Mock processes 1234 (user1), 5678 (user2, docker) and 9012 (root) with
+    /// the given SM usage and memory
+    fn processes(usage: [Option<f32>; 3], memory: [u64; 3]) -> Vec<GpuProcess> {
+        let mut processes = ProcessManager::mock_processes();
+        for (process, (usage, memory)) in processes.iter_mut().zip(usage.into_iter().zip(memory)) {
+            process.gpu_usage = usage;
+            process.memory_usage = memory;
+        }
+        processes
+    }
+
+    /// Records `power` watts on GPU `gpu` for one hour
+    fn record_hour(tracker: &mut EnergyTracker, start: Instant, gpu: usize, power: f32, processes: &[GpuProcess]) {
+        let processes: Vec<&GpuProcess> = processes.iter().collect();
+        tracker.record_at(start, gpu, Some(power), &processes);
+        tracker.record_at(start + Duration::from_secs(3600), gpu, Some(power), &processes);
+    }
+
+    fn energy(tracker: &EnergyTracker, pid: u32) -> f64 {
+        tracker.process_energy_wh(pid).unwrap_or(0.0)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn splits_by_sm_usage() {
+        let mut tracker = EnergyTracker::new();
+        let processes = processes([Some(60.0), Some(30.0), Some(10.0)], [1, 1000, 1]);
+        record_hour(&mut tracker, Instant::now(), 0, 200.0, &processes);
+
+        assert_close(energy(&tracker, 1234), 120.0);
+        assert_close(energy(&tracker, 5678), 60.0);
+        assert_close(energy(&tracker, 9012), 20.0);
+    }
+
+    #[test]
+    fn falls_back_to_memory_without_samples() {
+        let mut tracker = EnergyTracker::new();
+        let processes = processes([None, None, None], [300, 100, 0]);
+        record_hour(&mut tracker, Instant::now(), 0, 100.0, &processes);
+
+        assert_close(energy(&tracker, 1234), 75.0);
+        assert_close(energy(&tracker, 5678), 25.0);
+        assert_close(energy(&tracker, 9012), 0.0);
+    }
+
+    #[test]
+    fn idle_gpu_is_unattributed() {
+        let mut tracker = EnergyTracker::new();
+        record_hour(&mut tracker, Instant::now(), 0, 50.0, &[]);
+
+        let report = tracker.report(&settings());
+        assert_close(report.unattributed.energy_kwh, 0.05);
+        assert_close(report.total.energy_kwh, 0.05);
+        assert!(report.by_process.is_empty());
+    }
+
+    #[test]
+    fn gpus_are_timed_separately() {
+        let mut tracker = EnergyTracker::new();
+        let start = Instant::now();
+        let all = processes([Some(50.0), Some(50.0), None], [1, 1, 1]);
+        let (gpu0, gpu1) = all.split_at(1);
+
+        // GPU 1 starts half an hour later; its first tick only sets the baseline
+        let gpu0: Vec<&GpuProcess> = gpu0.iter().collect();
+        let gpu1: Vec<&GpuProcess> = gpu1.iter().collect();
+        tracker.record_at(start, 0, Some(100.0), &gpu0);
+        tracker.record_at(start + Duration::from_secs(1800), 1, Some(300.0), &gpu1);
+        tracker.record_at(start + Duration::from_secs(3600), 0, Some(100.0), &gpu0);
+        tracker.record_at(start + Duration::from_secs(3600), 1, Some(300.0), &gpu1);
+
+        assert_close(energy(&tracker, 1234), 100.0);
+        assert_close(energy(&tracker, 5678), 150.0);
+        assert_close(energy(&tracker, 9012), 0.0);
+    }
+
+    #[test]
+    fn report_groups_by_user_container_and_process() {
+        let mut tracker = EnergyTracker::new();
+        let processes = processes([Some(50.0), Some(25.0), Some(25.0)], [1, 1, 1]);
+        record_hour(&mut tracker, Instant::now(), 0, 400.0, &processes);
+
+        let report = tracker.report(&settings());
+        assert_close(report.total.energy_kwh, 0.4);
+        assert_close(report.total.cost, 0.2);
+        assert_close(report.total.carbon_kg, 0.16);
+        assert_eq!(report.total.processes, 3);
+
+        let names = |rows: &[EnergyUsage]| rows.iter().map(|r| (r.name.clone(), r.energy_kwh)).collect::<Vec<_>>();
+        assert_eq!(names(&report.by_user)[0], ("user1".to_string(), 0.2));
+        assert_eq!(report.by_user.len(), 3);
+        assert_eq!(names(&report.by_process)[0], ("python.exe (1234)".to_string(), 0.2));
+
+        let host = report.by_container.iter().find(|r| r.name == "host").unwrap();
+        assert_eq!(host.processes, 2);
+        assert_close(host.energy_kwh, 0.3);
+        assert_eq!(report.by_container.len(), 2);
+    }
+}
@@ -0,0 +1,125 @@
+use crossterm::event::{Event, EventStream, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{interval_at, Duration, Instant, MissedTickBehavior};
+use crate::sampler::Snapshot;
+
+/// Everything the UI loop reacts to, funnelled through one channel
+#[derive(Debug)]
+pub enum AppEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    Resize,
+    Tick,                 // Time to sample again
+    SampleCompleted,      // The sampler published a new snapshot
+    InputError(String),   // Terminal input stream failed
+}
+
+/// Owns the input, timer and sample forwarding tasks. Dropping it stops them.
+pub struct EventHandler {
+    receiver: mpsc::UnboundedReceiver<AppEvent>,
+    interval: watch::Sender<u64>,
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl EventHandler {
+    pub fn new(interval_ms: u64, samples: watch::Receiver<Arc<Snapshot>>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let (interval, interval_rx) = watch::channel(interval_ms);
+
+        let tasks = vec![
+            tokio::spawn(read_terminal(sender.clone())),
+            tokio::spawn(tick(sender.clone(), interval_rx)),
+            tokio::spawn(forward_samples(sender, samples)),
+        ];
+
+        Self { receiver, interval, tasks }
+    }
+
+    /// Wait for the next event. `None` once all tasks have stopped.
+    pub async fn next(&mut self) -> Option<AppEvent> {
+        self.receiver.recv().await
+    }
+
+    /// Take an already queued event without waiting, so bursts are handled
+    /// before the next redraw
+    pub fn try_next(&mut self) -> Option<AppEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Retune the tick timer. The next tick comes a full period from now.
+    pub fn set_interval(&self, interval_ms: u64) {
+        self.interval.send_if_modified(|current| {
+            let changed = *current != interval_ms;
+            *current = interval_ms;
+            changed
+        });
+    }
+}
+
+impl Drop for EventHandler {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn read_terminal(sender: mpsc::UnboundedSender<AppEvent>) {
+    let mut events = EventStream::new();
+
+    while let Some(event) = events.next().await {
+        let event = match event {
+            // Key release/repeat events are only reported on some terminals
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => AppEvent::Key(key),
+            // Plain pointer motion changes nothing on screen
+            Ok(Event::Mouse(mouse)) if mouse.kind != MouseEventKind::Moved => AppEvent::Mouse(mouse),
+            Ok(Event::Resize(_, _)) => AppEvent::Resize,
+            Ok(_) => continue,
+            Err(e) => AppEvent::InputError(e.to_string()),
+        };
+
+        if sender.send(event).is_err() {
+            break;
+        }
+    }
+}
+
+/// Only the first timer ticks immediately; a retuned one waits a full period
+/// so changing the interval doesn't sample twice
+async fn tick(sender: mpsc::UnboundedSender<AppEvent>, mut interval_ms: watch::Receiver<u64>) {
+    let mut timer = sampling_timer(*interval_ms.borrow(), true);
+
+    loop {
+        tokio::select! {
+            _ = timer.tick() => {
+                if sender.send(AppEvent::Tick).is_err() {
+                    break;
+                }
+            }
+            changed = interval_ms.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                timer = sampling_timer(*interval_ms.borrow(), false);
+            }
+        }
+    }
+}
+
+async fn forward_samples(sender: mpsc::UnboundedSender<AppEvent>, mut samples: watch::Receiver<Arc<Snapshot>>) {
+    while samples.changed().await.is_ok() {
+        if sender.send(AppEvent::SampleCompleted).is_err() {
+            break;
+        }
+    }
+}
+
+fn sampling_timer(period_ms: u64, immediate: bool) -> tokio::time::Interval {
+    let period = Duration::from_millis(period_ms);
+    let start = if immediate { Instant::now() } else { Instant::now() + period };
+    let mut timer = interval_at(start, period);
+    timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    timer
+}
@@ -3,6 +3,7 @@ use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 use anyhow::Result;
+use crate::energy::{EnergyReport, EnergyUsage};
 use crate::gpu::GpuInfo;
 use crate::process::GpuProcess;
 use crate::health::GpuHealthMetrics;
@@ -41,7 +42,7 @@ impl CsvExporter {
         let mut file = File::create(output_path)?;
         
         // CSV Header
-        writeln!(file, "timestamp,pid,user,command,gpu_usage_percent,memory_usage_mb,memory_usage_gb,encoder_usage_percent,decoder_usage_percent,priority,context_id,container_id,parent_pid")?;
+        writeln!(file, "timestamp,pid,user,command,gpu_usage_percent,memory_usage_mb,memory_usage_gb,encoder_usage_percent,decoder_usage_percent,priority,context_id,container_id,parent_pid,container_runtime,pod_namespace,pod_name")?;
         
         let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
         
@@ -49,23 +50,27 @@ impl CsvExporter {
         for process in processes {
             let memory_mb = process.memory_usage / (1024 * 1024);
             let memory_gb = memory_mb as f64 / 1024.0;
+            let pod = process.container.as_ref().and_then(|c| c.pod.as_ref());
             
             writeln!(
                 file,
-                "{},{},{},{},{:.1},{},{:.2},{:.1},{:.1},{},{},{},{}",
+                "{},{},{},{},{},{},{:.2},{},{},{},{},{},{},{},{},{}",
                 timestamp,
                 process.pid,
                 Self::escape_csv(&process.user),
                 Self::escape_csv(&process.command),
-                process.gpu_usage,
+                Self::cell(process.gpu_usage, |u| format!("{:.1}", u)),
                 memory_mb,
                 memory_gb,
-                process.encoder_usage,
-                process.decoder_usage,
+                Self::cell(process.encoder_usage, |u| format!("{:.1}", u)),
+                Self::cell(process.decoder_usage, |u| format!("{:.1}", u)),
                 process.priority,
                 process.context_id.map_or("".to_string(), |id| id.to_string()),
                 process.container_id.as_deref().unwrap_or(""),
-                process.parent_pid.map_or("".to_string(), |pid| pid.to_string())
+                process.parent_pid.map_or("".to_string(), |pid| pid.to_string()),
+                process.container.as_ref().map_or("", |c| c.runtime.text()),
+                Self::escape_csv(pod.and_then(|p| p.namespace.as_deref()).unwrap_or("")),
+                Self::escape_csv(pod.and_then(|p| p.name.as_deref()).unwrap_or(""))
             )?;
         }
         
@@ -79,30 +84,29 @@ impl CsvExporter {
         writeln!(file, "timestamp,gpu_name,utilization_percent,memory_used_mb,memory_total_mb,memory_usage_percent,temperature_c,power_draw_w,gpu_clock_mhz,memory_clock_mhz,fan_speed_percent,throttled,health_score,health_status")?;
         
         let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        let memory_used_mb = gpu.memory_used / (1024 * 1024);
-        let memory_total_mb = gpu.memory_total / (1024 * 1024);
-        let memory_usage_percent = (gpu.memory_used as f64 / gpu.memory_total as f64) * 100.0;
+        let mb = |bytes: u64| (bytes / (1024 * 1024)).to_string();
         
         let (health_score, health_status) = if let Some(h) = health {
             (h.overall_score.to_string(), h.status.text().to_string())
         } else {
-            ("N/A".to_string(), "Unknown".to_string())
+            (String::new(), "Unknown".to_string())
         };
         
+        // Metrics the GPU didn't report are left empty rather than 0
         writeln!(
             file,
-            "{},{},{:.1},{},{},{:.1},{:.1},{},{},{},{},{},{},{}",
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
             timestamp,
             Self::escape_csv(&gpu.name),
-            gpu.utilization,
-            memory_used_mb,
-            memory_total_mb,
-            memory_usage_percent,
-            gpu.temperature,
-            gpu.power_draw.map_or("N/A".to_string(), |p| format!("{:.1}", p)),
-            gpu.gpu_clock.map_or("N/A".to_string(), |c| c.to_string()),
-            gpu.memory_clock.map_or("N/A".to_string(), |c| c.to_string()),
-            gpu.fan_speed.map_or("N/A".to_string(), |f| format!("{:.0}", f)),
+            Self::cell(gpu.utilization, |u| format!("{:.1}", u)),
+            Self::cell(gpu.memory_used, mb),
+            Self::cell(gpu.memory_total, mb),
+            Self::cell(gpu.memory_percent(), |pct| format!("{:.1}", pct)),
+            Self::cell(gpu.temperature, |t| format!("{:.1}", t)),
+            Self::cell(gpu.power_draw, |p| format!("{:.1}", p)),
+            Self::cell(gpu.gpu_clock, |c| c.to_string()),
+            Self::cell(gpu.memory_clock, |c| c.to_string()),
+            Self::cell(gpu.fan_speed, |f| format!("{:.0}", f)),
             if gpu.throttled { "Yes" } else { "No" },
             health_score,
             health_status
@@ -132,21 +136,74 @@ impl CsvExporter {
         Ok(())
     }
     
+    pub fn export_energy_report_csv(report: &EnergyReport, output_path: &str) -> Result<()> {
+        let mut file = File::create(output_path)?;
+        
+        // CSV Header
+        writeln!(file, "session_start,generated_at,group,name,processes,energy_kwh,cost_{},carbon_kg", report.currency)?;
+        
+        let session_start = report.session_start.format("%Y-%m-%d %H:%M:%S");
+        let generated_at = report.generated_at.format("%Y-%m-%d %H:%M:%S");
+        
+        let groups: [(&str, &[EnergyUsage]); 5] = [
+            ("total", std::slice::from_ref(&report.total)),
+            ("idle", std::slice::from_ref(&report.unattributed)),
+            ("user", &report.by_user),
+            ("container", &report.by_container),
+            ("process", &report.by_process),
+        ];
+        
+        for (group, rows) in groups {
+            for row in rows {
+                writeln!(
+                    file,
+                    "{},{},{},{},{},{:.6},{:.4},{:.6}",
+                    session_start,
+                    generated_at,
+                    group,
+                    Self::escape_csv(&row.name),
+                    row.processes,
+                    row.energy_kwh,
+                    row.cost,
+                    row.carbon_kg
+                )?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    pub fn export_energy_report_json(report: &EnergyReport, output_path: &str) -> Result<()> {
+        let json = serde_json::to_string_pretty(report)?;
+        std::fs::write(output_path, json)?;
+        Ok(())
+    }
+    
     fn write_gpu_info(file: &mut File, gpu: &GpuInfo) -> Result<()> {
         writeln!(file, "=== GPU INFORMATION ===")?;
         writeln!(file, "Name,{}", gpu.name)?;
         writeln!(file, "Driver Version,{}", gpu.driver_version)?;
         writeln!(file, "CUDA Version,{}", gpu.cuda_version.as_deref().unwrap_or("N/A"))?;
-        writeln!(file, "Utilization,%{:.1}", gpu.utilization)?;
-        writeln!(file, "Memory Used,{} MB", gpu.memory_used / (1024 * 1024))?;
-        writeln!(file, "Memory Total,{} MB", gpu.memory_total / (1024 * 1024))?;
-        writeln!(file, "Memory Usage,{:.1}%", (gpu.memory_used as f64 / gpu.memory_total as f64) * 100.0)?;
-        writeln!(file, "Temperature,{:.1}°C", gpu.temperature)?;
+        writeln!(file, "Utilization,{}", Self::cell(gpu.utilization, |u| format!("%{:.1}", u)))?;
+        writeln!(file, "Memory Used,{}", Self::cell(gpu.memory_used, |used| format!("{} MB", used / (1024 * 1024))))?;
+        writeln!(file, "Memory Total,{}", Self::cell(gpu.memory_total, |total| format!("{} MB", total / (1024 * 1024))))?;
+        writeln!(file, "Memory Usage,{}", Self::cell(gpu.memory_percent(), |pct| format!("{:.1}%", pct)))?;
+        writeln!(file, "Temperature,{}", Self::cell(gpu.temperature, |t| format!("{:.1}°C", t)))?;
         
         if let Some(power) = gpu.power_draw {
             writeln!(file, "Power Draw,{:.1}W", power)?;
         }
         
+        if let Some(limits) = &gpu.power_limits {
+            writeln!(file, "Power Limit,{:.1}W", limits.enforced)?;
+            if let Some(default) = limits.default {
+                writeln!(file, "Default Power Limit,{:.1}W", default)?;
+            }
+            if let (Some(min), Some(max)) = (limits.min, limits.max) {
+                writeln!(file, "Power Limit Range,{:.1}W - {:.1}W", min, max)?;
+            }
+        }
+        
         if let Some(clock) = gpu.gpu_clock {
             writeln!(file, "GPU Clock,{} MHz", clock)?;
         }
@@ -159,6 +216,17 @@ impl CsvExporter {
             writeln!(file, "Fan Speed,{:.0}%", fan)?;
         }
         
+        for fan in &gpu.fans {
+            writeln!(
+                file,
+                "Fan {},{},{},{}",
+                fan.index,
+                Self::cell(fan.speed, |s| format!("{:.0}%", s)),
+                Self::cell(fan.target_speed, |s| format!("target {:.0}%", s)),
+                fan.policy.map_or("", |p| p.text())
+            )?;
+        }
+        
         writeln!(file, "Throttled,{}", if gpu.throttled { "Yes" } else { "No" })?;
         writeln!(file, "")?;
         
@@ -174,10 +242,12 @@ impl CsvExporter {
         writeln!(file, "")?;
         
         writeln!(file, "=== TEMPERATURE HEALTH ===")?;
-        writeln!(file, "Current Temperature,{:.1}°C", health.temperature.current)?;
+        writeln!(file, "Current Temperature,{}", Self::cell(health.temperature.current, |t| format!("{:.1}°C", t)))?;
         writeln!(file, "Temperature Trend (5min),{:+.1}°C", health.temperature.trend_5min)?;
         writeln!(file, "Time Above 80°C,{} minutes", health.temperature.time_above_80c / 60)?;
-        writeln!(file, "Peak Temperature Today,{:.1}°C", health.temperature.peak_today)?;
+        writeln!(file, "Peak Temperature Today,{}", Self::cell(health.temperature.peak_today, |t| format!("{:.1}°C", t)))?;
+        writeln!(file, "Fan Speed,{}", Self::cell(health.fans.speed, |s| format!("{:.0}%", s)))?;
+        writeln!(file, "Fan Trend (5min),{:+.0}%", health.fans.trend_5min)?;
         writeln!(file, "")?;
         
         writeln!(file, "=== POWER HEALTH ===")?;
@@ -185,6 +255,10 @@ impl CsvExporter {
         writeln!(file, "Power Efficiency,{:.2} util/W", health.power.efficiency)?;
         writeln!(file, "Power Spikes Count,{}", health.power.power_spikes)?;
         writeln!(file, "Average Power (1hr),{:.1}W", health.power.avg_draw_1hr)?;
+        if let Some(limit_usage) = health.power.limit_usage {
+            writeln!(file, "Power Limit Usage,{:.1}%", limit_usage)?;
+        }
+        writeln!(file, "Session Energy,{:.3} Wh", health.power.session_energy_wh)?;
         writeln!(file, "")?;
         
         writeln!(file, "=== MEMORY HEALTH ===")?;
@@ -205,14 +279,14 @@ impl CsvExporter {
             let memory_mb = process.memory_usage / (1024 * 1024);
             writeln!(
                 file,
-                "{},{},{},{:.1},{},{:.1},{:.1},{}",
+                "{},{},{},{},{},{},{},{}",
                 process.pid,
                 Self::escape_csv(&process.user),
                 Self::escape_csv(&process.command),
-                process.gpu_usage,
+                Self::cell(process.gpu_usage, |u| format!("{:.1}", u)),
                 memory_mb,
-                process.encoder_usage,
-                process.decoder_usage,
+                Self::cell(process.encoder_usage, |u| format!("{:.1}", u)),
+                Self::cell(process.decoder_usage, |u| format!("{:.1}", u)),
                 process.priority
             )?;
         }
@@ -220,6 +294,11 @@ impl CsvExporter {
         Ok(())
     }
     
+    /// A CSV cell for an optional metric, empty when it is missing
+    fn cell<T>(value: Option<T>, format: impl FnOnce(T) -> String) -> String {
+        value.map_or(String::new(), format)
+    }
+    
     fn escape_csv(value: &str) -> String {
         if value.contains(',') || value.contains('"') || value.contains('\n') {
             format!("\"{}\"", value.replace("\"", "\"\""))
@@ -0,0 +1,336 @@
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+use crate::gpu::{GpuInfo, GpuSelector};
+use crate::process::GpuProcess;
+
+/// Process list filter parsed from the `/` search prompt.
+///
+/// Whitespace separated terms must all match:
+/// - `python` matches command, user, PID or container (case-insensitive)
+/// - `user:alice`, `cmd:train`, `pid:1234`, `container:abc`, `host:node1` match one field
+/// - `gpu:1` / `gpu:0:2` match the device (or MIG instance) a process runs on
+/// - `mem>2G`, `usage>=50`, `pid<1000` compare numbers (`mem` defaults to MB)
+/// - `re:^py.*` or `/^py.*/` match a regex against command, user and container
+#[derive(Debug, Clone)]
+pub struct ProcessFilter {
+    terms: Vec<FilterTerm>,
+}
+
+#[derive(Debug, Clone)]
+enum FilterTerm {
+    Text(String),
+    Regex(Regex),
+    User(String),
+    Command(String),
+    Pid(u32),
+    Container(String),
+    Host(String),
+    Gpu(GpuSelector),
+    Compare(NumericField, Comparison, f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NumericField {
+    Memory,
+    Usage,
+    Pid,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+}
+
+/// Table column a highlight is computed for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterField {
+    User,
+    Command,
+}
+
+impl ProcessFilter {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let terms = input.split_whitespace()
+            .map(parse_term)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, process: &GpuProcess, gpus: &[GpuInfo]) -> bool {
+        self.terms.iter().all(|term| term_matches(term, process, gpus))
+    }
+
+    /// Byte ranges of `text` matched by the filter, sorted and merged
+    pub fn highlights(&self, field: FilterField, text: &str) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        
+        for term in &self.terms {
+            match term {
+                FilterTerm::Text(needle) => ranges.extend(find_all(text, needle)),
+                FilterTerm::Regex(regex) => ranges.extend(regex.find_iter(text).map(|m| m.range())),
+                FilterTerm::User(needle) if field == FilterField::User => ranges.extend(find_all(text, needle)),
+                FilterTerm::Command(needle) if field == FilterField::Command => ranges.extend(find_all(text, needle)),
+                _ => {}
+            }
+        }
+        
+        ranges.retain(|r| !r.is_empty());
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+fn parse_term(term: &str) -> Result<FilterTerm, String> {
+    if let Some(pattern) = term.strip_prefix("re:") {
+        return parse_regex(pattern);
+    }
+    if term.len() > 2 && term.starts_with('/') && term.ends_with('/') {
+        return parse_regex(&term[1..term.len() - 1]);
+    }
+    
+    // Comparisons are checked before `key:value` so `pid<10` isn't plain text
+    for (op_text, op) in [
+        (">=", Comparison::GreaterEqual),
+        ("<=", Comparison::LessEqual),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+        ("=", Comparison::Equal),
+    ] {
+        if let Some((key, value)) = term.split_once(op_text) {
+            let field = match key.to_ascii_lowercase().as_str() {
+                "mem" | "memory" | "vram" => NumericField::Memory,
+                "usage" | "gpu%" | "util" => NumericField::Usage,
+                "pid" => NumericField::Pid,
+                _ => continue,
+            };
+            let value = match field {
+                NumericField::Memory => parse_size(value)? as f64,
+                _ => value.parse::<f64>().map_err(|_| format!("invalid number '{}'", value))?,
+            };
+            return Ok(FilterTerm::Compare(field, op, value));
+        }
+    }
+    
+    if let Some((key, value)) = term.split_once(':') {
+        let value_lower = value.to_ascii_lowercase();
+        match key.to_ascii_lowercase().as_str() {
+            "user" | "u" => return Ok(FilterTerm::User(value_lower)),
+            "cmd" | "command" | "c" => return Ok(FilterTerm::Command(value_lower)),
+            "container" | "pod" => return Ok(FilterTerm::Container(value_lower)),
+            "host" | "node" => return Ok(FilterTerm::Host(value_lower)),
+            "pid" => {
+                return value.parse()
+                    .map(FilterTerm::Pid)
+                    .map_err(|_| format!("invalid PID '{}'", value));
+            }
+            "gpu" => return value.parse().map(FilterTerm::Gpu),
+            _ => {}
+        }
+    }
+    
+    Ok(FilterTerm::Text(term.to_ascii_lowercase()))
+}
+
+fn parse_regex(pattern: &str) -> Result<FilterTerm, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map(FilterTerm::Regex)
+        .map_err(|e| format!("invalid regex: {}", e))
+}
+
+/// `2G`, `1.5GB`, `500M`, `64k`; plain numbers are megabytes
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim().to_ascii_uppercase();
+    let value = value.strip_suffix('B').unwrap_or(&value);
+    let (number, multiplier) = match value.chars().last() {
+        Some('K') => (&value[..value.len() - 1], 1024.0),
+        Some('M') => (&value[..value.len() - 1], 1024.0 * 1024.0),
+        Some('G') => (&value[..value.len() - 1], 1024.0 * 1024.0 * 1024.0),
+        Some('T') => (&value[..value.len() - 1], 1024.0 * 1024.0 * 1024.0 * 1024.0),
+        _ => (value, 1024.0 * 1024.0),
+    };
+    
+    number.parse::<f64>()
+        .map(|n| (n * multiplier) as u64)
+        .map_err(|_| format!("invalid size '{}'", value))
+}
+
+fn term_matches(term: &FilterTerm, process: &GpuProcess, gpus: &[GpuInfo]) -> bool {
+    let container = process.container_label().unwrap_or_default();
+    
+    match term {
+        FilterTerm::Text(needle) => {
+            contains(&process.command, needle)
+                || contains(&process.user, needle)
+                || process.pid.to_string().contains(needle.as_str())
+                || contains(&container, needle)
+        }
+        FilterTerm::Regex(regex) => {
+            regex.is_match(&process.command) || regex.is_match(&process.user) || regex.is_match(&container)
+        }
+        FilterTerm::User(needle) => contains(&process.user, needle),
+        FilterTerm::Command(needle) => contains(&process.command, needle),
+        FilterTerm::Pid(pid) => process.pid == *pid,
+        FilterTerm::Container(needle) => {
+            contains(&container, needle)
+                || process.container.as_ref().is_some_and(|c| contains(&c.id, needle))
+        }
+        FilterTerm::Host(needle) => process.host.as_deref().is_some_and(|host| contains(host, needle)),
+        FilterTerm::Gpu(selector) => selector.resolve(gpus)
+            .and_then(|i| gpus.get(i))
+            .is_some_and(|gpu| gpu.runs_process(process)),
+        FilterTerm::Compare(field, op, value) => {
+            // Processes whose usage isn't sampled match no usage comparison
+            let actual = match field {
+                NumericField::Memory => process.memory_usage as f64,
+                NumericField::Usage => match process.gpu_usage {
+                    Some(usage) => usage as f64,
+                    None => return false,
+                },
+                NumericField::Pid => process.pid as f64,
+            };
+            match op {
+                Comparison::Less => actual < *value,
+                Comparison::LessEqual => actual <= *value,
+                Comparison::Equal => actual == *value,
+                Comparison::GreaterEqual => actual >= *value,
+                Comparison::Greater => actual > *value,
+            }
+        }
+    }
+}
+
+fn contains(haystack: &str, needle_lower: &str) -> bool {
+    haystack.to_ascii_lowercase().contains(needle_lower)
+}
+
+fn find_all(text: &str, needle_lower: &str) -> Vec<Range<usize>> {
+    if needle_lower.is_empty() {
+        return Vec::new();
+    }
+    
+    // ASCII lowercasing keeps byte offsets identical to the original text
+    let lower = text.to_ascii_lowercase();
+    lower.match_indices(needle_lower)
+        .map(|(start, m)| start..start + m.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessManager;
+
+    const MB: u64 = 1024 * 1024;
+    const GB: u64 = 1024 * MB;
+
+    /// PIDs of the mock processes matching `filter`
+    fn matching(filter: &str) -> Vec<u32> {
+        let filter = ProcessFilter::parse(filter).unwrap();
+        ProcessManager::mock_processes().iter()
+            .filter(|p| filter.matches(p, &[]))
+            .map(|p| p.pid)
+            .collect()
+    }
+
+    #[test]
+    fn plain_text_matches_any_field() {
+        assert_eq!(matching("PYTHON"), [1234]);
+        assert_eq!(matching("user2"), [5678]);
+        assert_eq!(matching("901"), [9012]);
+        assert_eq!(matching("docker-cont"), [5678]);
+        assert_eq!(matching(""), [1234, 5678, 9012]);
+    }
+
+    #[test]
+    fn field_terms() {
+        assert_eq!(matching("user:root"), [9012]);
+        assert_eq!(matching("cmd:blender"), [5678]);
+        assert_eq!(matching("pid:1234"), [1234]);
+        assert_eq!(matching("container:docker"), [5678]);
+        assert!(matching("host:node1").is_empty());
+        // Unknown keys are plain text
+        assert!(matching("foo:bar").is_empty());
+    }
+
+    #[test]
+    fn terms_must_all_match() {
+        assert_eq!(matching("user:user1 python"), [1234]);
+        assert!(matching("user:user1 blender").is_empty());
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(matching("mem>2G"), [1234]);
+        assert_eq!(matching("mem>=1.5G"), [1234, 5678]);
+        assert_eq!(matching("mem<=512"), [9012]);
+        assert_eq!(matching("usage>50"), [1234, 5678]);
+        assert_eq!(matching("util<30"), [9012]);
+        assert_eq!(matching("pid=5678"), [5678]);
+        assert_eq!(matching("pid<2000 usage>80"), [1234]);
+    }
+
+    #[test]
+    fn unsampled_usage_never_matches() {
+        let mut process = ProcessManager::mock_processes().remove(0);
+        process.gpu_usage = None;
+        for filter in ["usage>50", "usage<50", "usage=0"] {
+            assert!(!ProcessFilter::parse(filter).unwrap().matches(&process, &[]));
+        }
+        assert!(ProcessFilter::parse("pid=1234").unwrap().matches(&process, &[]));
+    }
+
+    #[test]
+    fn regexes() {
+        assert_eq!(matching("re:^py.*exe$"), [1234]);
+        assert_eq!(matching("/^(blender|ffmpeg)/"), [5678, 9012]);
+    }
+
+    #[test]
+    fn malformed_terms_are_errors() {
+        assert!(ProcessFilter::parse("pid:abc").is_err());
+        assert!(ProcessFilter::parse("pid>abc").is_err());
+        assert!(ProcessFilter::parse("usage>=high").is_err());
+        assert!(ProcessFilter::parse("mem>lots").is_err());
+        assert!(ProcessFilter::parse("mem>G").is_err());
+        assert!(ProcessFilter::parse("re:(unclosed").is_err());
+        assert!(ProcessFilter::parse("gpu:x").is_err());
+        assert!(ProcessFilter::parse("gpu:0:2").is_ok());
+    }
+
+    #[test]
+    fn size_units() {
+        assert_eq!(parse_size("2G"), Ok(2 * GB));
+        assert_eq!(parse_size("2gb"), Ok(2 * GB));
+        assert_eq!(parse_size("1.5G"), Ok(3 * GB / 2));
+        assert_eq!(parse_size("500M"), Ok(500 * MB));
+        assert_eq!(parse_size("64k"), Ok(64 * 1024));
+        assert_eq!(parse_size("1T"), Ok(1024 * GB));
+        assert_eq!(parse_size("256"), Ok(256 * MB));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("12X").is_err());
+    }
+
+    #[test]
+    fn highlights_are_merged() {
+        let filter = ProcessFilter::parse("py thon cmd:exe user:py").unwrap();
+        assert_eq!(filter.highlights(FilterField::Command, "python.exe"), [0..6, 7..10]);
+        assert_eq!(filter.highlights(FilterField::User, "python.exe"), vec![0..6]);
+    }
+}
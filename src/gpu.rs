@@ -1,24 +1,254 @@
-use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use crate::nvidia_smi::{NvidiaSmi, SmiError};
+use crate::process::GpuProcess;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GpuInfo {
     pub index: usize,
     pub name: String,
     pub driver_version: String,
     pub cuda_version: Option<String>,
-    pub utilization: f32,
-    pub memory_used: u64,
-    pub memory_total: u64,
-    pub temperature: f32,
-    pub fan_speed: Option<f32>,
+    // Metrics are `None` when the device doesn't report them or reading
+    // them failed; `sensor_errors` tells which
+    pub utilization: Option<f32>,
+    pub memory_used: Option<u64>,
+    pub memory_total: Option<u64>,
+    pub temperature: Option<f32>,
+    pub fan_speed: Option<f32>,    // Average over `fans` when the device has several
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fans: Vec<FanInfo>,
     pub power_draw: Option<f32>,
+    pub power_limits: Option<PowerLimits>,
+    pub total_energy_mj: Option<u64>, // Energy counter since driver load, in millijoules
     pub gpu_clock: Option<u32>,
     pub memory_clock: Option<u32>,
+    pub app_gpu_clock: Option<u32>,     // Application clocks (MHz), the target under load
+    pub app_memory_clock: Option<u32>,
+    pub compute_mode: Option<ComputeMode>,
+    #[serde(default)]
+    pub encoder_utilization: Option<f32>,  // NVENC busy %
+    #[serde(default)]
+    pub decoder_utilization: Option<f32>,  // NVDEC busy %
+    #[serde(default)]
+    pub encoder: Option<EncoderStats>,
     pub throttled: bool,
     pub vendor: GpuVendor,
+    pub mig: Option<MigInstance>,  // Set when this entry is a MIG instance of GPU `index`
+    #[serde(default)]
+    pub host: Option<String>,      // Node the GPU belongs to when watching remote agents
+    #[serde(default)]
+    pub mock: bool,                // Demo data shown when no GPU backend is available
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sensor_errors: BTreeMap<String, SensorError>,  // Why metrics are missing from this sample, by field name
+}
+
+/// A MIG (Multi-Instance GPU) partition, reported as a child of its parent GPU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigInstance {
+    pub mig_index: u32,
+    pub gpu_instance_id: u32,
+    pub compute_instance_id: u32,
+    pub uuid: Option<String>,
+}
+
+/// Active NVENC sessions of a GPU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncoderStats {
+    pub session_count: u32,
+    pub average_fps: u32,
+    pub average_latency_us: u32,
+    pub sessions: Vec<EncoderSession>,  // Can be shorter than `session_count`: not every backend or codec is described
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncoderSession {
+    pub session_id: u32,
+    pub pid: u32,
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    pub latency_us: u32,
 }
 
+/// A process the driver reports as holding a context on a device
+#[derive(Debug, Clone)]
+pub struct DeviceProcess {
+    pub pid: u32,
+    pub gpu_index: usize,
+    pub gpu_instance_id: Option<u32>,
+    pub compute_instance_id: Option<u32>,
+    pub memory_used: Option<u64>,
+    pub gpu_usage: Option<f32>,      // SM share of the process, when the driver samples it
+    pub encoder_usage: Option<f32>,  // NVENC/NVDEC share of the process, when the driver samples it
+    pub decoder_usage: Option<f32>,
+}
+
+/// Device chosen with `--gpu`: a GPU index, `GPU:MIG` index pair or MIG UUID
 #[derive(Debug, Clone, PartialEq)]
+pub enum GpuSelector {
+    Index(usize),
+    Mig(usize, u32),
+    Uuid(String),
+}
+
+impl FromStr for GpuSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with("MIG-") {
+            return Ok(GpuSelector::Uuid(s.to_string()));
+        }
+        
+        let parse_index = |v: &str| v.parse::<usize>().map_err(|_| format!("invalid GPU index '{}'", v));
+        match s.split_once(':') {
+            Some((gpu, mig)) => {
+                let mig = mig.parse::<u32>().map_err(|_| format!("invalid MIG index '{}'", mig))?;
+                Ok(GpuSelector::Mig(parse_index(gpu)?, mig))
+            }
+            None => Ok(GpuSelector::Index(parse_index(s)?)),
+        }
+    }
+}
+
+impl GpuSelector {
+    /// Position of the selected device in the list returned by `get_gpu_info`
+    pub fn resolve(&self, gpus: &[GpuInfo]) -> Option<usize> {
+        gpus.iter().position(|gpu| match self {
+            GpuSelector::Index(index) => gpu.index == *index && gpu.mig.is_none(),
+            GpuSelector::Mig(index, mig_index) => {
+                gpu.index == *index && gpu.mig.as_ref().is_some_and(|m| m.mig_index == *mig_index)
+            }
+            GpuSelector::Uuid(uuid) => gpu.mig.as_ref().and_then(|m| m.uuid.as_ref()) == Some(uuid),
+        })
+    }
+}
+
+impl GpuInfo {
+    /// `index` or `index:mig` on its own host
+    pub fn local_id(&self) -> String {
+        match &self.mig {
+            Some(mig) => format!("{}:{}", self.index, mig.mig_index),
+            None => self.index.to_string(),
+        }
+    }
+
+    /// Why the reading of `sensor` (a field name) is missing from this sample
+    pub fn sensor_error(&self, sensor: &str) -> Option<&SensorError> {
+        self.sensor_errors.get(sensor).or_else(|| self.sensor_errors.get("device"))
+    }
+
+    /// Why metric `sensor` has no value: "error" when reading it failed this
+    /// sample, otherwise "unsupported"
+    pub fn missing_reason(&self, sensor: &str) -> &'static str {
+        match self.sensor_error(sensor) {
+            Some(SensorError::Failed(_)) => "error",
+            _ => "unsupported",
+        }
+    }
+
+    /// Memory in use as a percentage
+    pub fn memory_percent(&self) -> Option<f64> {
+        match (self.memory_used, self.memory_total) {
+            (Some(used), Some(total)) if total > 0 => Some(used as f64 / total as f64 * 100.0),
+            _ => None,
+        }
+    }
+
+    /// A device NVML lists but can't open
+    #[cfg(feature = "nvidia")]
+    fn unreadable(index: usize, driver_version: &str, readings: SensorReadings) -> Self {
+        GpuInfo {
+            index,
+            name: format!("GPU {}", index),
+            driver_version: driver_version.to_string(),
+            cuda_version: None,
+            utilization: None,
+            memory_used: None,
+            memory_total: None,
+            temperature: None,
+            fan_speed: None,
+            fans: Vec::new(),
+            power_draw: None,
+            power_limits: None,
+            total_energy_mj: None,
+            gpu_clock: None,
+            memory_clock: None,
+            app_gpu_clock: None,
+            app_memory_clock: None,
+            compute_mode: None,
+            encoder_utilization: None,
+            decoder_utilization: None,
+            encoder: None,
+            throttled: false,
+            vendor: GpuVendor::Nvidia,
+            mig: None,
+            host: None,
+            mock: false,
+            sensor_errors: readings.into_errors(),
+        }
+    }
+
+    pub fn display_id(&self) -> String {
+        let id = self.local_id();
+        match &self.host {
+            Some(host) => format!("{}/{}", host, id),
+            None => id,
+        }
+    }
+
+    /// Whether the process runs on this device. A parent GPU owns the
+    /// processes of all of its MIG instances.
+    pub fn runs_process(&self, process: &GpuProcess) -> bool {
+        if process.gpu_index != Some(self.index) || process.host != self.host {
+            return false;
+        }
+        
+        match &self.mig {
+            Some(mig) => {
+                process.gpu_instance_id == Some(mig.gpu_instance_id)
+                    && process.compute_instance_id == Some(mig.compute_instance_id)
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FanInfo {
+    pub index: u32,
+    pub speed: Option<f32>,         // Percent of the fan's maximum
+    pub target_speed: Option<f32>,  // What the driver (or a manual setting) asks the fan for
+    pub policy: Option<FanPolicy>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FanPolicy {
+    Auto,    // Driver follows its temperature curve
+    Manual,  // Fixed speed set by the user
+}
+
+impl FanPolicy {
+    pub fn text(&self) -> &'static str {
+        match self {
+            FanPolicy::Auto => "auto",
+            FanPolicy::Manual => "manual",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerLimits {
+    pub enforced: f32,          // Watts, the limit the driver is currently applying
+    pub default: Option<f32>,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GpuVendor {
     Nvidia,
     Amd,
@@ -26,17 +256,234 @@ pub enum GpuVendor {
     Unknown,
 }
 
+/// Which processes may create compute contexts on a device
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ComputeMode {
+    Default,           // Any number of processes
+    ExclusiveProcess,  // One process at a time
+    Prohibited,        // No compute contexts
+}
+
+impl ComputeMode {
+    pub fn text(&self) -> &'static str {
+        match self {
+            ComputeMode::Default => "Default",
+            ComputeMode::ExclusiveProcess => "Exclusive Process",
+            ComputeMode::Prohibited => "Prohibited",
+        }
+    }
+    
+    pub fn next(&self) -> ComputeMode {
+        match self {
+            ComputeMode::Default => ComputeMode::ExclusiveProcess,
+            ComputeMode::ExclusiveProcess => ComputeMode::Prohibited,
+            ComputeMode::Prohibited => ComputeMode::Default,
+        }
+    }
+}
+
+/// What a GPU backend found at startup
+#[derive(Debug, Clone)]
+pub struct BackendStatus {
+    pub name: &'static str,
+    pub state: BackendState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendState {
+    Available(String),    // What it found, e.g. "2 GPUs, driver 535.129.03"
+    Unavailable(String),  // Why it can't be used, e.g. "libnvidia-ml.so not found"
+    Skipped(String),      // Not tried because another backend covers the GPUs
+}
+
+impl BackendState {
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            BackendState::Available(_) => "✅",
+            BackendState::Unavailable(_) => "❌",
+            BackendState::Skipped(_) => "⏭️",
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            BackendState::Available(detail) | BackendState::Unavailable(detail) | BackendState::Skipped(detail) => detail,
+        }
+    }
+}
+
+/// Why a single reading of a device is missing from a sample
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+pub enum SensorError {
+    #[error("not supported")]
+    NotSupported,    // The device or driver never reports it
+    #[error("{0}")]
+    Failed(String),  // Transient failure; read again on the next sample
+}
+
+#[cfg(feature = "nvidia")]
+impl From<nvml_wrapper::error::NvmlError> for SensorError {
+    fn from(error: nvml_wrapper::error::NvmlError) -> Self {
+        use nvml_wrapper::error::NvmlError;
+        
+        match error {
+            NvmlError::NotSupported | NvmlError::FunctionNotFound => SensorError::NotSupported,
+            e => SensorError::Failed(e.to_string()),
+        }
+    }
+}
+
+/// Records why readings of one device failed, so the rest of the device
+/// can still be reported
+#[derive(Debug, Default)]
+pub struct SensorReadings {
+    errors: BTreeMap<String, SensorError>,
+}
+
+impl SensorReadings {
+    pub fn read<T, E: Into<SensorError>>(&mut self, sensor: &str, result: std::result::Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.errors.insert(sensor.to_string(), e.into());
+                None
+            }
+        }
+    }
+
+    pub fn into_errors(self) -> BTreeMap<String, SensorError> {
+        self.errors
+    }
+}
+
+/// Errors of the GPU backends and device controls
+#[derive(Debug, thiserror::Error)]
+pub enum GpuError {
+    #[cfg(feature = "nvidia")]
+    #[error("NVML: {0}")]
+    Nvml(#[from] nvml_wrapper::error::NvmlError),
+    #[error(transparent)]
+    NvidiaSmi(#[from] SmiError),
+    #[error("No GPU backend available and --no-mock is set:\n{0}\nRun `gputop doctor` for details.")]
+    NoBackend(String),
+    #[error("Permission denied: root is required to {action} on GPU {gpu}")]
+    PermissionDenied { action: &'static str, gpu: usize },
+    #[error("Not supported: GPU {gpu} can't {action}")]
+    ControlNotSupported { action: &'static str, gpu: usize },
+    #[error("Failed to {action} on GPU {gpu}: {reason}")]
+    ControlFailed { action: &'static str, gpu: usize, reason: String },
+    #[error("GPU {gpu} application clock is already at its {limit}")]
+    ClockLimit { gpu: usize, limit: &'static str },
+    #[error("{what} of GPU {gpu} requires an NVIDIA GPU with NVML")]
+    RequiresNvml { what: &'static str, gpu: usize },
+}
+
+type Result<T, E = GpuError> = std::result::Result<T, E>;
+
+pub const NVML_BACKEND: &str = "NVML";
+pub const NVIDIA_SMI_BACKEND: &str = "nvidia-smi";
+
 pub struct GpuManager {
     #[cfg(feature = "nvidia")]
     nvml: Option<nvml_wrapper::Nvml>,
+    // Raw NVML bindings for the MIG calls nvml-wrapper doesn't expose
+    #[cfg(feature = "nvidia")]
+    mig_lib: Option<nvml_wrapper_sys::bindings::NvmlLib>,
+    #[cfg(feature = "nvidia")]
+    fan_lib: Option<fans::FanLib>,
+    smi: Option<NvidiaSmi>,  // Used when NVML isn't available
+    backends: Vec<BackendStatus>,
 }
 
 impl GpuManager {
-    pub async fn new() -> Result<Self> {
-        Ok(Self {
+    /// Probes the backends in order of preference. Without any, the
+    /// manager serves a mock GPU, unless `allow_mock` is false.
+    pub async fn new(allow_mock: bool) -> Result<Self> {
+        let mut backends = Vec::new();
+        
+        #[cfg(feature = "nvidia")]
+        let nvml = match nvml_wrapper::Nvml::init() {
+            Ok(nvml) => match nvml.device_count() {
+                Ok(count) if count > 0 => {
+                    let driver = nvml.sys_driver_version().unwrap_or_else(|_| "unknown".to_string());
+                    backends.push(BackendStatus {
+                        name: NVML_BACKEND,
+                        state: BackendState::Available(format!("{} GPU(s), driver {}", count, driver)),
+                    });
+                    Some(nvml)
+                }
+                Ok(_) => {
+                    backends.push(BackendStatus { name: NVML_BACKEND, state: BackendState::Unavailable("no GPUs found".to_string()) });
+                    None
+                }
+                Err(e) => {
+                    backends.push(BackendStatus { name: NVML_BACKEND, state: BackendState::Unavailable(format!("listing GPUs failed: {}", e)) });
+                    None
+                }
+            },
+            Err(e) => {
+                backends.push(BackendStatus { name: NVML_BACKEND, state: BackendState::Unavailable(nvml_init_error(&e)) });
+                None
+            }
+        };
+        #[cfg(not(feature = "nvidia"))]
+        backends.push(BackendStatus {
+            name: NVML_BACKEND,
+            state: BackendState::Unavailable("built without the `nvidia` feature".to_string()),
+        });
+        
+        #[cfg(feature = "nvidia")]
+        let nvml_available = nvml.is_some();
+        #[cfg(not(feature = "nvidia"))]
+        let nvml_available = false;
+        
+        let smi = if nvml_available {
+            backends.push(BackendStatus { name: NVIDIA_SMI_BACKEND, state: BackendState::Skipped("NVML is in use".to_string()) });
+            None
+        } else {
+            match NvidiaSmi::probe() {
+                Ok((smi, gpus)) => {
+                    backends.push(BackendStatus { name: NVIDIA_SMI_BACKEND, state: BackendState::Available(describe_gpus(&gpus)) });
+                    Some(smi)
+                }
+                Err(e) => {
+                    backends.push(BackendStatus { name: NVIDIA_SMI_BACKEND, state: BackendState::Unavailable(format!("{:#}", e)) });
+                    None
+                }
+            }
+        };
+        
+        for name in ["AMD (ROCm)", "Intel"] {
+            backends.push(BackendStatus { name, state: BackendState::Unavailable("not supported yet".to_string()) });
+        }
+        
+        let manager = Self {
             #[cfg(feature = "nvidia")]
-            nvml: nvml_wrapper::Nvml::init().ok(),
-        })
+            mig_lib: nvml.as_ref().and_then(|_| mig::load_library()),
+            #[cfg(feature = "nvidia")]
+            fan_lib: nvml.as_ref().and_then(|_| fans::FanLib::load()),
+            #[cfg(feature = "nvidia")]
+            nvml,
+            smi,
+            backends,
+        };
+        
+        if manager.uses_mock_data() && !allow_mock {
+            let reasons: Vec<String> = manager.backends.iter()
+                .map(|b| format!("  {} {}: {}", b.state.emoji(), b.name, b.state.detail()))
+                .collect();
+            return Err(GpuError::NoBackend(reasons.join("\n")));
+        }
+        Ok(manager)
+    }
+
+    pub fn backends(&self) -> &[BackendStatus] {
+        &self.backends
+    }
+
+    /// True when no backend found a GPU and a mock GPU is shown instead
+    pub fn uses_mock_data(&self) -> bool {
+        !self.backends.iter().any(|b| matches!(b.state, BackendState::Available(_)))
     }
 
     pub async fn get_gpu_info(&self) -> Result<Vec<GpuInfo>> {
@@ -47,71 +494,609 @@ impl GpuManager {
             gpus.extend(self.get_nvidia_info(nvml)?);
         }
         
+        if let Some(smi) = &self.smi {
+            gpus.extend(smi.gpus()?);
+        }
+        
         // Add AMD and Intel support here
         self.get_fallback_info(&mut gpus).await?;
         
         Ok(gpus)
     }
 
+    /// Processes holding a context on any device, with the MIG instance they run on
+    pub async fn get_device_processes(&self) -> Result<Vec<DeviceProcess>> {
+        #[cfg(feature = "nvidia")]
+        if let Some(nvml) = &self.nvml {
+            return self.get_nvidia_processes(nvml);
+        }
+        
+        if let Some(smi) = &self.smi {
+            return Ok(smi.device_processes()?);
+        }
+        
+        Ok(Vec::new())
+    }
+
+    /// Sets the compute mode of physical GPU `gpu_index`. Requires root.
+    pub fn set_compute_mode(&self, gpu_index: usize, mode: ComputeMode) -> Result<()> {
+        #[cfg(feature = "nvidia")]
+        if let Some(nvml) = &self.nvml {
+            use nvml_wrapper::enum_wrappers::device::ComputeMode as Nvml;
+            
+            let mut device = nvml.device_by_index(gpu_index as u32)?;
+            let mode = match mode {
+                ComputeMode::Default => Nvml::Default,
+                ComputeMode::ExclusiveProcess => Nvml::ExclusiveProcess,
+                ComputeMode::Prohibited => Nvml::Prohibited,
+            };
+            return device.set_compute_mode(mode)
+                .map_err(|e| control_error("change the compute mode", gpu_index, e));
+        }
+        
+        let _ = mode;
+        Err(GpuError::RequiresNvml { what: "Changing the compute mode", gpu: gpu_index })
+    }
+
+    /// Moves the application graphics clock one supported step up or down,
+    /// at the highest supported memory clock. Returns the new (graphics,
+    /// memory) clocks in MHz.
+    pub fn step_application_clocks(&self, gpu_index: usize, up: bool) -> Result<(u32, u32)> {
+        #[cfg(feature = "nvidia")]
+        if let Some(nvml) = &self.nvml {
+            use nvml_wrapper::enum_wrappers::device::Clock;
+            
+            let mut device = nvml.device_by_index(gpu_index as u32)?;
+            let action = "change application clocks";
+            let memory_clock = device.supported_memory_clocks()
+                .map_err(|e| control_error(action, gpu_index, e))?
+                .into_iter()
+                .max()
+                .ok_or(GpuError::ControlNotSupported { action, gpu: gpu_index })?;
+            let mut graphics_clocks = device.supported_graphics_clocks(memory_clock)
+                .map_err(|e| control_error(action, gpu_index, e))?;
+            graphics_clocks.sort_unstable();
+            graphics_clocks.dedup();
+            
+            let current = device.applications_clock(Clock::Graphics).unwrap_or(0);
+            let next = if up {
+                graphics_clocks.iter().find(|&&clock| clock > current)
+            } else {
+                graphics_clocks.iter().rev().find(|&&clock| clock < current)
+            };
+            let Some(&graphics_clock) = next else {
+                return Err(GpuError::ClockLimit { gpu: gpu_index, limit: if up { "maximum" } else { "minimum" } });
+            };
+            
+            device.set_applications_clocks(memory_clock, graphics_clock)
+                .map_err(|e| control_error(action, gpu_index, e))?;
+            return Ok((graphics_clock, memory_clock));
+        }
+        
+        let _ = up;
+        Err(GpuError::RequiresNvml { what: "Application clocks", gpu: gpu_index })
+    }
+
+    pub fn reset_application_clocks(&self, gpu_index: usize) -> Result<()> {
+        #[cfg(feature = "nvidia")]
+        if let Some(nvml) = &self.nvml {
+            let mut device = nvml.device_by_index(gpu_index as u32)?;
+            return device.reset_applications_clocks()
+                .map_err(|e| control_error("reset application clocks", gpu_index, e));
+        }
+        
+        Err(GpuError::RequiresNvml { what: "Application clocks", gpu: gpu_index })
+    }
+
+    /// One failing device or sensor is reported in `GpuInfo::sensor_errors`
+    /// instead of failing the whole sample
     #[cfg(feature = "nvidia")]
     fn get_nvidia_info(&self, nvml: &nvml_wrapper::Nvml) -> Result<Vec<GpuInfo>> {
+        use nvml_wrapper::enum_wrappers::device::{Clock, TemperatureSensor};
+        
         let mut gpus = Vec::new();
         let device_count = nvml.device_count()?;
+        let driver_version = nvml.sys_driver_version().unwrap_or_else(|_| "unknown".to_string());
+        let cuda_version = nvml.sys_cuda_driver_version().ok().map(|v| format!("{}.{}", v / 1000, (v % 1000) / 10));
         
         for i in 0..device_count {
-            let device = nvml.device_by_index(i)?;
-            let name = device.name()?;
-            let memory_info = device.memory_info()?;
-            let utilization = device.utilization_rates()?.gpu;
-            let temperature = device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu)?;
+            let mut readings = SensorReadings::default();
+            let device = match nvml.device_by_index(i) {
+                Ok(device) => device,
+                Err(e) => {
+                    readings.read::<(), _>("device", Err(e));
+                    gpus.push(GpuInfo::unreadable(i as usize, &driver_version, readings));
+                    continue;
+                }
+            };
             
-            let fan_speed = device.fan_speed(0).ok().map(|f| f as f32);
-            let power_draw = device.power_usage().ok().map(|p| p as f32 / 1000.0);
-            let gpu_clock = device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics).ok();
-            let memory_clock = device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory).ok();
+            let name = readings.read("name", device.name()).unwrap_or_else(|| format!("GPU {}", i));
+            let memory_info = readings.read("memory", device.memory_info());
+            let utilization = readings.read("utilization", device.utilization_rates()).map(|u| u.gpu);
+            let temperature = readings.read("temperature", device.temperature(TemperatureSensor::Gpu));
+            let fans = self.get_nvidia_fans(&device, &mut readings);
+            let speeds: Vec<f32> = fans.iter().filter_map(|fan| fan.speed).collect();
+            let fan_speed = (!speeds.is_empty()).then(|| speeds.iter().sum::<f32>() / speeds.len() as f32);
+            let power_draw = readings.read("power_draw", device.power_usage()).map(|p| p as f32 / 1000.0);
+            let gpu_clock = readings.read("gpu_clock", device.clock_info(Clock::Graphics));
+            let memory_clock = readings.read("memory_clock", device.clock_info(Clock::Memory));
+            let encoder_utilization = readings.read("encoder_utilization", device.encoder_utilization())
+                .map(|u| u.utilization as f32);
+            let decoder_utilization = readings.read("decoder_utilization", device.decoder_utilization())
+                .map(|u| u.utilization as f32);
+            let encoder = readings.read("encoder", device.encoder_stats()).map(|stats| EncoderStats {
+                session_count: stats.session_count,
+                average_fps: stats.average_fps,
+                average_latency_us: stats.average_latency,
+                sessions: Self::get_nvidia_encoder_sessions(&device),
+            });
+            
+            let power_limits = Self::get_nvidia_power_limits(&device, &mut readings);
+            let total_energy_mj = readings.read("total_energy_mj", device.total_energy_consumption());
+            let app_gpu_clock = readings.read("app_gpu_clock", device.applications_clock(Clock::Graphics));
+            let app_memory_clock = readings.read("app_memory_clock", device.applications_clock(Clock::Memory));
+            let compute_mode = readings.read("compute_mode", device.compute_mode()).and_then(|mode| {
+                use nvml_wrapper::enum_wrappers::device::ComputeMode as Nvml;
+                match mode {
+                    Nvml::Default => Some(ComputeMode::Default),
+                    Nvml::ExclusiveProcess => Some(ComputeMode::ExclusiveProcess),
+                    Nvml::Prohibited => Some(ComputeMode::Prohibited),
+                    Nvml::ExclusiveThread => None,  // Removed from the driver
+                }
+            });
             
             gpus.push(GpuInfo {
                 index: i as usize,
                 name,
-                driver_version: nvml.sys_driver_version()?,
-                cuda_version: nvml.sys_cuda_driver_version().ok().map(|v| format!("{}.{}", v / 1000, (v % 1000) / 10)),
-                utilization: utilization as f32,
-                memory_used: memory_info.used,
-                memory_total: memory_info.total,
-                temperature: temperature as f32,
+                driver_version: driver_version.clone(),
+                cuda_version: cuda_version.clone(),
+                utilization: utilization.map(|u| u as f32),
+                memory_used: memory_info.as_ref().map(|m| m.used),
+                memory_total: memory_info.as_ref().map(|m| m.total),
+                temperature: temperature.map(|t| t as f32),
                 fan_speed,
+                fans,
                 power_draw,
+                power_limits,
+                total_energy_mj,
                 gpu_clock,
                 memory_clock,
+                app_gpu_clock,
+                app_memory_clock,
+                compute_mode,
+                encoder_utilization,
+                decoder_utilization,
+                encoder,
                 throttled: false, // TODO: Implement throttling detection
                 vendor: GpuVendor::Nvidia,
+                mig: None,
+                host: None,
+                mock: false,
+                sensor_errors: readings.into_errors(),
             });
+            
+            if let Some(lib) = &self.mig_lib {
+                let parent = gpus.last().cloned().expect("parent GPU was just pushed");
+                gpus.extend(mig::get_instances(lib, nvml, &device, &parent));
+            }
         }
         
         Ok(gpus)
     }
 
+    #[cfg(feature = "nvidia")]
+    fn get_nvidia_processes(&self, nvml: &nvml_wrapper::Nvml) -> Result<Vec<DeviceProcess>> {
+        use nvml_wrapper::enums::device::UsedGpuMemory;
+        
+        let mut processes = Vec::new();
+        for i in 0..nvml.device_count()? {
+            // Reported through `get_nvidia_info`
+            let Ok(device) = nvml.device_by_index(i) else {
+                continue;
+            };
+            let compute = device.running_compute_processes().unwrap_or_default();
+            let graphics = device.running_graphics_processes().unwrap_or_default();
+            let utilization = Self::get_nvidia_process_utilization(&device);
+            
+            for info in compute.into_iter().chain(graphics) {
+                // A process using both compute and graphics is listed twice
+                if processes.iter().any(|p: &DeviceProcess| p.pid == info.pid && p.gpu_index == i as usize) {
+                    continue;
+                }
+                processes.push(DeviceProcess {
+                    pid: info.pid,
+                    gpu_index: i as usize,
+                    gpu_instance_id: info.gpu_instance_id,
+                    compute_instance_id: info.compute_instance_id,
+                    memory_used: match info.used_gpu_memory {
+                        UsedGpuMemory::Used(bytes) => Some(bytes),
+                        UsedGpuMemory::Unavailable => None,
+                    },
+                    gpu_usage: utilization.as_ref().map(|usage| usage.get(&info.pid).map_or(0.0, |u| u.0)),
+                    encoder_usage: utilization.as_ref().map(|usage| usage.get(&info.pid).map_or(0.0, |u| u.1)),
+                    decoder_usage: utilization.as_ref().map(|usage| usage.get(&info.pid).map_or(0.0, |u| u.2)),
+                });
+            }
+        }
+        
+        Ok(processes)
+    }
+
+    /// Every fan of the device. Fans that don't report a speed are left out;
+    /// one whose reading fails is listed without it.
+    #[cfg(feature = "nvidia")]
+    fn get_nvidia_fans(&self, device: &nvml_wrapper::Device, readings: &mut SensorReadings) -> Vec<FanInfo> {
+        // Drivers before R460 can't count fans but do report fan 0
+        let count = device.num_fans().unwrap_or(1);
+        
+        let mut fans = Vec::new();
+        for index in 0..count {
+            let speed = device.fan_speed(index).map_err(SensorError::from);
+            if matches!(speed, Err(SensorError::NotSupported)) {
+                continue;
+            }
+            fans.push(FanInfo {
+                index,
+                speed: readings.read("fan_speed", speed).map(|s| s as f32),
+                target_speed: self.fan_lib.as_ref().and_then(|lib| lib.target_speed(device, index)).map(|s| s as f32),
+                policy: self.fan_lib.as_ref().and_then(|lib| lib.policy(device, index)),
+            });
+        }
+        fans
+    }
+
+    /// Per-process (SM %, encoder %, decoder %) over the last second.
+    /// Processes that left the engines idle have no sample. `None` when the
+    /// device doesn't sample per-process utilization.
+    #[cfg(feature = "nvidia")]
+    fn get_nvidia_process_utilization(device: &nvml_wrapper::Device) -> Option<std::collections::HashMap<u32, (f32, f32, f32)>> {
+        use nvml_wrapper::error::NvmlError;
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+        
+        // Sample timestamps are CPU time in microseconds
+        let since = SystemTime::now().duration_since(UNIX_EPOCH).ok()?
+            .saturating_sub(Duration::from_secs(1))
+            .as_micros() as u64;
+        let samples = match device.process_utilization_stats(since) {
+            Ok(samples) => samples,
+            Err(NvmlError::NotFound) => Vec::new(),  // No samples in the window
+            Err(_) => return None,
+        };
+        
+        let mut latest: std::collections::HashMap<u32, (u64, f32, f32, f32)> = Default::default();
+        for sample in samples {
+            let entry = latest.entry(sample.pid).or_insert((0, 0.0, 0.0, 0.0));
+            if sample.timestamp >= entry.0 {
+                *entry = (sample.timestamp, sample.sm_util as f32, sample.enc_util as f32, sample.dec_util as f32);
+            }
+        }
+        Some(latest.into_iter().map(|(pid, (_, sm, enc, dec))| (pid, (sm, enc, dec))).collect())
+    }
+
+    /// Sessions with a codec this NVML binding doesn't know (e.g. AV1) make
+    /// the whole query fail; they still count in `EncoderStats`
+    #[cfg(feature = "nvidia")]
+    fn get_nvidia_encoder_sessions(device: &nvml_wrapper::Device) -> Vec<EncoderSession> {
+        use nvml_wrapper::enum_wrappers::device::EncoderType;
+        
+        device.encoder_sessions()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|session| EncoderSession {
+                session_id: session.session_id,
+                pid: session.pid,
+                codec: match session.codec_type {
+                    EncoderType::H264 => "H.264",
+                    EncoderType::HEVC => "HEVC",
+                }.to_string(),
+                width: session.hres,
+                height: session.vres,
+                fps: session.average_fps,
+                latency_us: session.average_latency,
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "nvidia")]
+    fn get_nvidia_power_limits(device: &nvml_wrapper::Device, readings: &mut SensorReadings) -> Option<PowerLimits> {
+        // NVML reports all limits in milliwatts
+        let to_watts = |mw: u32| mw as f32 / 1000.0;
+
+        let enforced = readings.read(
+            "power_limits",
+            device.enforced_power_limit().or_else(|_| device.power_management_limit()),
+        )?;
+        let constraints = readings.read("power_limit_range", device.power_management_limit_constraints());
+
+        Some(PowerLimits {
+            enforced: to_watts(enforced),
+            default: readings.read("power_limit_default", device.power_management_limit_default()).map(to_watts),
+            min: constraints.as_ref().map(|c| to_watts(c.min_limit)),
+            max: constraints.as_ref().map(|c| to_watts(c.max_limit)),
+        })
+    }
+
     async fn get_fallback_info(&self, gpus: &mut Vec<GpuInfo>) -> Result<()> {
-        // If no GPUs detected, add a mock GPU for demonstration
-        if gpus.is_empty() {
+        // Without any backend, add a mock GPU for demonstration
+        if gpus.is_empty() && self.uses_mock_data() {
             gpus.push(GpuInfo {
                 index: 0,
                 name: "Mock GPU".to_string(),
                 driver_version: "1.0.0".to_string(),
                 cuda_version: None,
-                utilization: 45.0,
-                memory_used: 2048 * 1024 * 1024, // 2GB
-                memory_total: 8192 * 1024 * 1024, // 8GB
-                temperature: 65.0,
-                fan_speed: Some(60.0),
+                utilization: Some(45.0),
+                memory_used: Some(2048 * 1024 * 1024), // 2GB
+                memory_total: Some(8192 * 1024 * 1024), // 8GB
+                temperature: Some(65.0),
+                fan_speed: Some(59.0),
+                fans: vec![
+                    FanInfo { index: 0, speed: Some(60.0), target_speed: Some(60.0), policy: Some(FanPolicy::Auto) },
+                    FanInfo { index: 1, speed: Some(58.0), target_speed: Some(60.0), policy: Some(FanPolicy::Auto) },
+                ],
                 power_draw: Some(150.0),
+                power_limits: Some(PowerLimits {
+                    enforced: 200.0,
+                    default: Some(200.0),
+                    min: Some(100.0),
+                    max: Some(250.0),
+                }),
+                total_energy_mj: None,
                 gpu_clock: Some(1500),
                 memory_clock: Some(7000),
+                app_gpu_clock: None,
+                app_memory_clock: None,
+                compute_mode: None,
+                encoder_utilization: Some(30.0),
+                decoder_utilization: Some(12.0),
+                encoder: Some(EncoderStats {
+                    session_count: 1,
+                    average_fps: 60,
+                    average_latency_us: 2500,
+                    sessions: vec![EncoderSession {
+                        session_id: 1,
+                        pid: 9012,  // The mock ffmpeg process
+                        codec: "H.264".to_string(),
+                        width: 1920,
+                        height: 1080,
+                        fps: 60,
+                        latency_us: 2500,
+                    }],
+                }),
                 throttled: false,
                 vendor: GpuVendor::Unknown,
+                mig: None,
+                host: None,
+                mock: true,
+                sensor_errors: BTreeMap::new(),
             });
         }
         
         Ok(())
     }
 }
+
+/// "2 GPU(s), driver 535.129.03"
+pub fn describe_gpus(gpus: &[GpuInfo]) -> String {
+    let driver = gpus.first().map_or("unknown", |gpu| gpu.driver_version.as_str());
+    format!("{} GPU(s), driver {}", gpus.len(), driver)
+}
+
+/// Why NVML couldn't be initialised, in terms of what to fix
+#[cfg(feature = "nvidia")]
+fn nvml_init_error(error: &nvml_wrapper::error::NvmlError) -> String {
+    use nvml_wrapper::error::NvmlError;
+    
+    match error {
+        NvmlError::LibloadingError(_) | NvmlError::LibraryNotFound => format!(
+            "{} not found (is the NVIDIA driver installed, or mounted into the container?)", mig::LIB_PATH
+        ),
+        NvmlError::DriverNotLoaded => "the NVIDIA kernel driver is not loaded".to_string(),
+        NvmlError::NoPermission => "insufficient permissions to open the GPU device files (/dev/nvidia*)".to_string(),
+        NvmlError::LibRmVersionMismatch => {
+            "driver and library versions don't match (reload the kernel module or reboot after a driver upgrade)".to_string()
+        }
+        NvmlError::FailedToLoadSymbol(_) | NvmlError::FunctionNotFound => {
+            format!("{} is too old for this build", mig::LIB_PATH)
+        }
+        e => format!("initialisation failed: {}", e),
+    }
+}
+
+/// Classifies NVML errors from device controls for the status bar
+#[cfg(feature = "nvidia")]
+fn control_error(action: &'static str, gpu_index: usize, error: nvml_wrapper::error::NvmlError) -> GpuError {
+    use nvml_wrapper::error::NvmlError;
+    
+    match error {
+        NvmlError::NoPermission => GpuError::PermissionDenied { action, gpu: gpu_index },
+        NvmlError::NotSupported => GpuError::ControlNotSupported { action, gpu: gpu_index },
+        e => GpuError::ControlFailed { action, gpu: gpu_index, reason: e.to_string() },
+    }
+}
+
+#[cfg(feature = "nvidia")]
+mod mig {
+    use super::{GpuInfo, MigInstance, SensorReadings};
+    use nvml_wrapper::{Device, Nvml};
+    use nvml_wrapper_sys::bindings::{
+        nvmlReturn_enum_NVML_SUCCESS as NVML_SUCCESS, NvmlLib, NVML_DEVICE_MIG_ENABLE,
+    };
+
+    #[cfg(target_os = "windows")]
+    pub(super) const LIB_PATH: &str = "nvml.dll";
+
+    #[cfg(not(target_os = "windows"))]
+    pub(super) const LIB_PATH: &str = "libnvidia-ml.so";
+
+    /// Loads the NVML library a second time for the MIG entry points. The
+    /// library is already initialised by `Nvml::init`, so no init is needed.
+    /// Returns `None` on drivers that predate MIG.
+    pub fn load_library() -> Option<NvmlLib> {
+        // SAFETY: loading libnvidia-ml runs no initialisers beyond the ones
+        // `Nvml::init` already ran when it loaded the same library
+        let lib = unsafe { NvmlLib::new(LIB_PATH).ok()? };
+        let supported = lib.nvmlDeviceGetMigMode.is_ok()
+            && lib.nvmlDeviceGetMaxMigDeviceCount.is_ok()
+            && lib.nvmlDeviceGetMigDeviceHandleByIndex.is_ok()
+            && lib.nvmlDeviceGetGpuInstanceId.is_ok()
+            && lib.nvmlDeviceGetComputeInstanceId.is_ok();
+        
+        supported.then_some(lib)
+    }
+
+    pub fn get_instances(lib: &NvmlLib, nvml: &Nvml, device: &Device, parent: &GpuInfo) -> Vec<GpuInfo> {
+        let mut instances = Vec::new();
+        
+        // SAFETY: the handle stays valid while `device` borrows `nvml`
+        let handle = unsafe { device.handle() };
+        
+        // The entry points below were all checked in `load_library`, so the
+        // `NvmlLib` wrappers don't panic on a missing symbol. Each takes the
+        // device handle and writes one `unsigned int` (or handle) through a
+        // pointer to a local of that type, matching the NVML headers.
+        
+        let (mut current_mode, mut pending_mode) = (0, 0);
+        // SAFETY: see above; both out-pointers are `c_uint` locals
+        if unsafe { lib.nvmlDeviceGetMigMode(handle, &mut current_mode, &mut pending_mode) } != NVML_SUCCESS
+            || current_mode != NVML_DEVICE_MIG_ENABLE
+        {
+            return instances;
+        }
+        
+        let mut max_count = 0;
+        // SAFETY: see above; `max_count` is a `c_uint` local
+        if unsafe { lib.nvmlDeviceGetMaxMigDeviceCount(handle, &mut max_count) } != NVML_SUCCESS {
+            return instances;
+        }
+        
+        for mig_index in 0..max_count {
+            // Unpopulated slots return NOT_FOUND
+            let mut mig_handle = std::ptr::null_mut();
+            // SAFETY: see above; `mig_index` is below the count NVML just
+            // reported and `mig_handle` is an `nvmlDevice_t` local
+            if unsafe { lib.nvmlDeviceGetMigDeviceHandleByIndex(handle, mig_index, &mut mig_handle) } != NVML_SUCCESS {
+                continue;
+            }
+            
+            let (mut gpu_instance_id, mut compute_instance_id) = (0, 0);
+            // SAFETY: see above; `mig_handle` was just returned by NVML and
+            // both out-pointers are `c_uint` locals
+            let ids_read = unsafe {
+                lib.nvmlDeviceGetGpuInstanceId(mig_handle, &mut gpu_instance_id) == NVML_SUCCESS
+                    && lib.nvmlDeviceGetComputeInstanceId(mig_handle, &mut compute_instance_id) == NVML_SUCCESS
+            };
+            if !ids_read {
+                continue;
+            }
+            
+            // SAFETY: `mig_handle` is a live MIG device handle of `nvml`
+            let mig_device = unsafe { Device::new(mig_handle, nvml) };
+            let Ok(memory_info) = mig_device.memory_info() else {
+                continue;
+            };
+            
+            // Most drivers don't report per-instance utilization
+            let mut readings = SensorReadings::default();
+            let utilization = readings.read("utilization", mig_device.utilization_rates());
+            let mut sensor_errors = parent.sensor_errors.clone();
+            sensor_errors.remove("utilization");
+            sensor_errors.extend(readings.into_errors());
+            
+            instances.push(GpuInfo {
+                name: mig_device.name().unwrap_or_else(|_| format!("{} MIG {}", parent.name, mig_index)),
+                utilization: utilization.map(|u| u.gpu as f32),
+                memory_used: Some(memory_info.used),
+                memory_total: Some(memory_info.total),
+                // Power, fans, energy and media engine totals are
+                // shared with the parent and must not be counted twice
+                fan_speed: None,
+                fans: Vec::new(),
+                power_draw: None,
+                power_limits: None,
+                total_energy_mj: None,
+                encoder_utilization: None,
+                decoder_utilization: None,
+                encoder: None,
+                mig: Some(MigInstance {
+                    mig_index,
+                    gpu_instance_id,
+                    compute_instance_id,
+                    uuid: mig_device.uuid().ok(),
+                }),
+                sensor_errors,
+                ..parent.clone()
+            });
+        }
+        
+        instances
+    }
+}
+
+#[cfg(feature = "nvidia")]
+mod fans {
+    use super::FanPolicy;
+    use nvml_wrapper::Device;
+    use nvml_wrapper_sys::bindings::{nvmlDevice_t, nvmlReturn_enum_NVML_SUCCESS as NVML_SUCCESS, nvmlReturn_t};
+    use std::os::raw::c_uint;
+    
+    type GetTargetFanSpeed = unsafe extern "C" fn(nvmlDevice_t, c_uint, *mut c_uint) -> nvmlReturn_t;
+    type GetFanControlPolicy = unsafe extern "C" fn(nvmlDevice_t, c_uint, *mut c_uint) -> nvmlReturn_t;
+    
+    // nvmlFanControlPolicy_t
+    const NVML_FAN_POLICY_TEMPERATURE_CONTINOUS_SW: c_uint = 0;
+    const NVML_FAN_POLICY_MANUAL: c_uint = 1;
+    
+    /// Fan entry points nvml-wrapper doesn't expose, looked up one by one
+    /// since they depend on the driver version (control policy needs R495).
+    /// Like the MIG library, NVML is already initialised by `Nvml::init`.
+    pub struct FanLib {
+        _lib: libloading::Library,  // Keeps the function pointers valid
+        target_speed: Option<GetTargetFanSpeed>,
+        control_policy: Option<GetFanControlPolicy>,
+    }
+    
+    impl FanLib {
+        pub fn load() -> Option<Self> {
+            // SAFETY: loading libnvidia-ml runs no initialisers beyond the
+            // ones `Nvml::init` already ran when it loaded the same library
+            let lib = unsafe { libloading::Library::new(super::mig::LIB_PATH).ok()? };
+            // SAFETY: both aliases match the NVML header prototypes:
+            // `nvmlReturn_t f(nvmlDevice_t device, unsigned int fan, unsigned int *out)`.
+            // The pointers are copied out but stay valid because `_lib`
+            // keeps the library loaded for as long as `FanLib` lives.
+            let (target_speed, control_policy) = unsafe {
+                (
+                    lib.get::<GetTargetFanSpeed>(b"nvmlDeviceGetTargetFanSpeed\0").ok().map(|f| *f),
+                    lib.get::<GetFanControlPolicy>(b"nvmlDeviceGetFanControlPolicy_v2\0").ok().map(|f| *f),
+                )
+            };
+            Some(Self { _lib: lib, target_speed, control_policy })
+        }
+        
+        pub fn target_speed(&self, device: &Device, fan: u32) -> Option<u32> {
+            let get = self.target_speed?;
+            let mut speed = 0;
+            // SAFETY: `get` matches the C prototype (see `load`), the handle
+            // is live while `device` is borrowed, and NVML writes a single
+            // `unsigned int` to `speed`. An out-of-range fan is an error
+            // return, not a write.
+            let result = unsafe { get(device.handle(), fan, &mut speed) };
+            (result == NVML_SUCCESS).then_some(speed)
+        }
+        
+        pub fn policy(&self, device: &Device, fan: u32) -> Option<FanPolicy> {
+            let get = self.control_policy?;
+            let mut policy = 0;
+            // SAFETY: as in `target_speed`; `nvmlFanControlPolicy_t` is an
+            // `unsigned int`, so `policy` is large enough
+            if unsafe { get(device.handle(), fan, &mut policy) } != NVML_SUCCESS {
+                return None;
+            }
+            match policy {
+                NVML_FAN_POLICY_TEMPERATURE_CONTINOUS_SW => Some(FanPolicy::Auto),
+                NVML_FAN_POLICY_MANUAL => Some(FanPolicy::Manual),
+                _ => None,
+            }
+        }
+    }
+}
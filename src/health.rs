@@ -1,7 +1,10 @@
 use chrono::{DateTime, Local, TimeZone};
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use crate::gpu::{FanPolicy, GpuInfo};
+use crate::settings::HealthThresholds;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum HealthStatus {
     Excellent,  // 🟢 All metrics optimal
     Good,       // 🔵 Minor concerns
@@ -9,18 +12,19 @@ pub enum HealthStatus {
     Critical,   // 🔴 Immediate action required
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AlertType {
     TemperatureHigh,
     TemperatureCritical,
     ThermalThrottling,
     PowerSpike,
+    PowerLimit,
     MemoryLeakSuspected,
     ClockInstability,
     FanIssue,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthAlert {
     pub alert_type: AlertType,
     pub message: String,
@@ -30,25 +34,35 @@ pub struct HealthAlert {
     pub threshold: Option<f32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemperatureMetrics {
-    pub current: f32,
+    pub current: Option<f32>,  // None when the GPU reports no temperature
     pub max_safe: f32,
     pub critical: f32,
     pub trend_5min: f32,  // degrees change over 5 minutes
     pub time_above_80c: u64,  // seconds spent above 80°C
-    pub peak_today: f32,
+    pub peak_today: Option<f32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FanHealthMetrics {
+    pub speed: Option<f32>,   // Average over the GPU's fans, None without fans
+    pub trend_5min: f32,      // Percentage points change over 5 minutes
+    pub flat_fans: Vec<u32>,  // Fans whose speed hasn't moved in that window (and aren't at 100%)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PowerMetrics {
     pub current_draw: f32,
     pub efficiency: f32,  // performance per watt
     pub power_spikes: u32,  // number of sudden power increases
     pub avg_draw_1hr: f32,
+    pub power_limit: Option<f32>,  // enforced limit in watts, when the driver reports one
+    pub limit_usage: Option<f32>,  // current draw as percentage of the enforced limit
+    pub session_energy_wh: f64,  // energy consumed since monitoring started
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryHealthMetrics {
     pub usage_trend: f32,  // MB change per minute
     pub fragmentation_score: f32,  // 0.0-1.0, higher = more fragmented
@@ -56,11 +70,13 @@ pub struct MemoryHealthMetrics {
     pub peak_usage_today: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GpuHealthMetrics {
     pub overall_score: f32,  // 0.0-100.0
     pub status: HealthStatus,
     pub temperature: TemperatureMetrics,
+    #[serde(default)]
+    pub fans: FanHealthMetrics,
     pub power: PowerMetrics,
     pub memory: MemoryHealthMetrics,
     pub thermal_throttling_detected: bool,
@@ -68,52 +84,93 @@ pub struct GpuHealthMetrics {
     pub alerts: Vec<HealthAlert>,
 }
 
+// Fan check: a rise of this many °C in 5 minutes should move the fans by
+// more than this many percentage points
+const FAN_TEMPERATURE_RISE: f32 = 10.0;
+const FAN_FLAT_TOLERANCE: f32 = 3.0;
+
 pub struct HealthMonitor {
     history_window: VecDeque<HealthSnapshot>,
     alert_history: VecDeque<HealthAlert>,
     monitoring_start: DateTime<Local>,
+    thresholds: HealthThresholds,
+    energy: HashMap<usize, SessionEnergy>, // keyed by GPU index
+}
+
+/// Energy accumulated since monitoring started. Uses the hardware energy
+/// counter when available and falls back to integrating power samples.
+#[derive(Debug, Default)]
+struct SessionEnergy {
+    counter_start_mj: Option<u64>,
+    integrated_wh: f64,
+    last_sample: Option<(DateTime<Local>, f32)>,
+}
+
+impl SessionEnergy {
+    fn record(&mut self, timestamp: DateTime<Local>, power_draw: Option<f32>, counter_mj: Option<u64>) -> f64 {
+        if let Some(power) = power_draw {
+            if let Some((last_time, last_power)) = self.last_sample {
+                let hours = (timestamp - last_time).num_milliseconds().max(0) as f64 / 3_600_000.0;
+                self.integrated_wh += (last_power + power) as f64 / 2.0 * hours;
+            }
+            self.last_sample = Some((timestamp, power));
+        }
+
+        match counter_mj {
+            Some(counter) => {
+                let start = *self.counter_start_mj.get_or_insert(counter);
+                counter.saturating_sub(start) as f64 / 3_600_000.0
+            }
+            None => self.integrated_wh,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 struct HealthSnapshot {
     timestamp: DateTime<Local>,
-    temperature: f32,
+    temperature: Option<f32>,
+    fan_speeds: Vec<(u32, f32)>,  // By fan index
     power_draw: f32,
-    memory_used: u64,
-    gpu_utilization: f32,
+    power_limit: Option<f32>,
+    memory_used: Option<u64>,
+    gpu_utilization: Option<f32>,
     clock_speeds: (u32, u32), // gpu_clock, memory_clock
     is_throttling: bool,
 }
 
 impl HealthMonitor {
-    pub fn new() -> Self {
+    pub fn new(thresholds: HealthThresholds) -> Self {
         Self {
             history_window: VecDeque::with_capacity(3600), // 1 hour at 1Hz
             alert_history: VecDeque::with_capacity(100),
             monitoring_start: Local::now(),
+            thresholds,
+            energy: HashMap::new(),
         }
     }
 
-    pub fn update_metrics(&mut self, 
-        temperature: f32,
-        power_draw: Option<f32>,
-        memory_used: u64,
-        memory_total: u64,
-        gpu_utilization: f32,
-        gpu_clock: Option<u32>,
-        memory_clock: Option<u32>,
-        is_throttling: bool,
-    ) -> GpuHealthMetrics {
-        
+    pub fn set_thresholds(&mut self, thresholds: HealthThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    pub fn update_metrics(&mut self, gpu: &GpuInfo) -> GpuHealthMetrics {
+        let temperature = gpu.temperature;
+        let memory_total = gpu.memory_total.unwrap_or(0);
+        let is_throttling = gpu.throttled;
+
         let snapshot = HealthSnapshot {
             timestamp: Local::now(),
             temperature,
-            power_draw: power_draw.unwrap_or(0.0),
-            memory_used,
-            gpu_utilization,
-            clock_speeds: (gpu_clock.unwrap_or(0), memory_clock.unwrap_or(0)),
+            fan_speeds: Self::fan_speeds(gpu),
+            power_draw: gpu.power_draw.unwrap_or(0.0),
+            power_limit: gpu.power_limits.as_ref().map(|l| l.enforced),
+            memory_used: gpu.memory_used,
+            gpu_utilization: gpu.utilization,
+            clock_speeds: (gpu.gpu_clock.unwrap_or(0), gpu.memory_clock.unwrap_or(0)),
             is_throttling,
         };
+        let session_energy_wh = self.energy.entry(gpu.index).or_default().record(snapshot.timestamp, gpu.power_draw, gpu.total_energy_mj);
 
         self.history_window.push_back(snapshot.clone());
         
@@ -124,12 +181,14 @@ impl HealthMonitor {
 
         // Calculate health metrics
         let temperature_metrics = self.calculate_temperature_metrics(&snapshot);
-        let power_metrics = self.calculate_power_metrics(&snapshot);
+        let fan_metrics = self.calculate_fan_metrics(&snapshot);
+        let power_metrics = self.calculate_power_metrics(&snapshot, session_energy_wh);
         let memory_metrics = self.calculate_memory_metrics(&snapshot, memory_total);
         
         // Generate alerts
         let mut alerts = Vec::new();
         self.check_temperature_alerts(&temperature_metrics, &mut alerts);
+        self.check_fan_alerts(gpu, &temperature_metrics, &fan_metrics, &mut alerts);
         self.check_power_alerts(&power_metrics, &mut alerts);
         self.check_memory_alerts(&memory_metrics, &mut alerts);
         
@@ -139,7 +198,7 @@ impl HealthMonitor {
                 message: "GPU is thermal throttling - performance reduced".to_string(),
                 severity: HealthStatus::Warning,
                 timestamp: Local::now(),
-                value: Some(temperature),
+                value: temperature,
                 threshold: Some(83.0),
             });
         }
@@ -166,6 +225,7 @@ impl HealthMonitor {
             overall_score,
             status,
             temperature: temperature_metrics,
+            fans: fan_metrics,
             power: power_metrics,
             memory: memory_metrics,
             thermal_throttling_detected: is_throttling,
@@ -179,14 +239,16 @@ impl HealthMonitor {
         
         // Calculate 5-minute trend
         let five_min_ago = current.timestamp - chrono::Duration::minutes(5);
-        let trend_5min = self.history_window.iter()
-            .find(|s| s.timestamp >= five_min_ago)
-            .map(|s| temp - s.temperature)
+        let trend_5min = temp
+            .and_then(|temp| self.history_window.iter()
+                .filter(|s| s.timestamp >= five_min_ago)
+                .find_map(|s| s.temperature)
+                .map(|past| temp - past))
             .unwrap_or(0.0);
 
         // Count time above 80°C in last hour
         let time_above_80c = self.history_window.iter()
-            .filter(|s| s.temperature > 80.0)
+            .filter(|s| s.temperature.is_some_and(|t| t > 80.0))
             .count() as u64; // seconds
 
         // Find peak temperature today
@@ -195,25 +257,71 @@ impl HealthMonitor {
         
         let peak_today = self.history_window.iter()
             .filter(|s| s.timestamp >= today_start)
-            .map(|s| s.temperature)
-            .fold(temp, f32::max);
+            .filter_map(|s| s.temperature)
+            .chain(temp)
+            .reduce(f32::max);
 
         TemperatureMetrics {
             current: temp,
-            max_safe: 80.0,
-            critical: 90.0,
+            max_safe: self.thresholds.temperature_warning,
+            critical: self.thresholds.temperature_critical,
             trend_5min,
             time_above_80c,
             peak_today,
         }
     }
 
-    fn calculate_power_metrics(&self, current: &HealthSnapshot) -> PowerMetrics {
+    /// Per-fan speeds, or the single reading of backends that don't list fans
+    fn fan_speeds(gpu: &GpuInfo) -> Vec<(u32, f32)> {
+        if gpu.fans.is_empty() {
+            return gpu.fan_speed.map(|speed| (0, speed)).into_iter().collect();
+        }
+        gpu.fans.iter()
+            .filter_map(|fan| Some((fan.index, fan.speed?)))
+            .collect()
+    }
+
+    fn calculate_fan_metrics(&self, current: &HealthSnapshot) -> FanHealthMetrics {
+        let speeds = &current.fan_speeds;
+        let average = |speeds: &[(u32, f32)]| {
+            (!speeds.is_empty()).then(|| speeds.iter().map(|(_, s)| s).sum::<f32>() / speeds.len() as f32)
+        };
+        
+        // Same window as the temperature trend, so the two can be compared
+        let five_min_ago = current.timestamp - chrono::Duration::minutes(5);
+        let window: Vec<&HealthSnapshot> = self.history_window.iter()
+            .filter(|s| s.timestamp >= five_min_ago)
+            .collect();
+        let trend_5min = average(speeds)
+            .zip(window.iter().find_map(|s| average(&s.fan_speeds)))
+            .map_or(0.0, |(now, past)| now - past);
+        
+        let flat_fans = speeds.iter()
+            .filter(|(_, speed)| *speed < 100.0)  // A maxed out fan can't go any higher
+            .filter(|(index, speed)| {
+                let past = window.iter()
+                    .find_map(|s| s.fan_speeds.iter().find(|(i, _)| i == index).map(|(_, speed)| *speed));
+                past.is_some_and(|past| (speed - past).abs() <= FAN_FLAT_TOLERANCE)
+            })
+            .map(|(index, _)| *index)
+            .collect();
+
+        FanHealthMetrics {
+            speed: average(speeds),
+            trend_5min,
+            flat_fans,
+        }
+    }
+
+    fn calculate_power_metrics(&self, current: &HealthSnapshot, session_energy_wh: f64) -> PowerMetrics {
         let power = current.power_draw;
         let utilization = current.gpu_utilization;
         
         // Calculate efficiency (utilization per watt)
-        let efficiency = if power > 0.0 { utilization / power } else { 0.0 };
+        let efficiency = match utilization {
+            Some(utilization) if power > 0.0 => utilization / power,
+            _ => 0.0,
+        };
         
         // Count power spikes (>20W increase in <10 seconds)
         let power_spikes = self.history_window.iter()
@@ -232,11 +340,18 @@ impl HealthMonitor {
                 .sum::<f32>() / self.history_window.len() as f32
         };
 
+        let limit_usage = current.power_limit
+            .filter(|limit| *limit > 0.0)
+            .map(|limit| power / limit * 100.0);
+
         PowerMetrics {
             current_draw: power,
             efficiency,
             power_spikes,
             avg_draw_1hr,
+            power_limit: current.power_limit,
+            limit_usage,
+            session_energy_wh,
         }
     }
 
@@ -245,18 +360,19 @@ impl HealthMonitor {
         
         // Calculate usage trend (MB change per minute)
         let one_min_ago = current.timestamp - chrono::Duration::minutes(1);
-        let usage_trend = self.history_window.iter()
-            .find(|s| s.timestamp >= one_min_ago)
-            .map(|s| (current_usage as f32 - s.memory_used as f32) / (1024.0 * 1024.0))
+        let usage_trend = current_usage
+            .and_then(|usage| self.history_window.iter()
+                .filter(|s| s.timestamp >= one_min_ago)
+                .find_map(|s| s.memory_used)
+                .map(|past| (usage as f32 - past as f32) / (1024.0 * 1024.0)))
             .unwrap_or(0.0);
 
         // Simple fragmentation estimation based on usage patterns
-        let usage_variance = if self.history_window.len() > 10 {
-            let recent_usage: Vec<f32> = self.history_window.iter()
-                .rev().take(60)
-                .map(|s| s.memory_used as f32)
-                .collect();
-            
+        let recent_usage: Vec<f32> = self.history_window.iter()
+            .rev().take(60)
+            .filter_map(|s| s.memory_used.map(|used| used as f32))
+            .collect();
+        let usage_variance = if recent_usage.len() > 10 {
             let mean = recent_usage.iter().sum::<f32>() / recent_usage.len() as f32;
             let variance = recent_usage.iter()
                 .map(|x| (x - mean).powi(2))
@@ -280,9 +396,10 @@ impl HealthMonitor {
         
         let peak_usage_today = self.history_window.iter()
             .filter(|s| s.timestamp >= today_start)
-            .map(|s| s.memory_used)
+            .filter_map(|s| s.memory_used)
+            .chain(current_usage)
             .max()
-            .unwrap_or(current_usage);
+            .unwrap_or(0);
 
         MemoryHealthMetrics {
             usage_trend,
@@ -301,9 +418,11 @@ impl HealthMonitor {
         let mut score: f32 = 100.0;
 
         // Temperature penalties
-        if temp.current > 90.0 { score -= 30.0; }
-        else if temp.current > 85.0 { score -= 20.0; }
-        else if temp.current > 80.0 { score -= 10.0; }
+        if let Some(current) = temp.current {
+            if current > 90.0 { score -= 30.0; }
+            else if current > 85.0 { score -= 20.0; }
+            else if current > 80.0 { score -= 10.0; }
+        }
         
         if temp.trend_5min > 10.0 { score -= 15.0; } // Rapidly heating
         if temp.time_above_80c > 1800 { score -= 10.0; } // >30min above 80°C
@@ -317,8 +436,11 @@ impl HealthMonitor {
 
         if memory.fragmentation_score > 0.7 { score -= 15.0; }
 
-        // Power efficiency penalties
-        if power.efficiency < 0.5 { score -= 10.0; } // Low efficiency
+        // Power penalties, relative to the enforced power limit
+        if let Some(limit_usage) = power.limit_usage {
+            if limit_usage >= self.thresholds.power_critical { score -= 15.0; }
+            else if limit_usage >= self.thresholds.power_warning { score -= 5.0; }
+        }
         if power.power_spikes > 5 { score -= 5.0; } // Unstable power
 
         score.max(0.0).min(100.0)
@@ -340,22 +462,26 @@ impl HealthMonitor {
     }
 
     fn check_temperature_alerts(&self, temp: &TemperatureMetrics, alerts: &mut Vec<HealthAlert>) {
-        if temp.current >= temp.critical {
+        let Some(current) = temp.current else {
+            return;
+        };
+        
+        if current >= temp.critical {
             alerts.push(HealthAlert {
                 alert_type: AlertType::TemperatureCritical,
-                message: format!("CRITICAL: GPU temperature {}°C exceeds safe limits!", temp.current),
+                message: format!("CRITICAL: GPU temperature {}°C exceeds safe limits!", current),
                 severity: HealthStatus::Critical,
                 timestamp: Local::now(),
-                value: Some(temp.current),
+                value: Some(current),
                 threshold: Some(temp.critical),
             });
-        } else if temp.current >= temp.max_safe {
+        } else if current >= temp.max_safe {
             alerts.push(HealthAlert {
                 alert_type: AlertType::TemperatureHigh,
-                message: format!("WARNING: GPU temperature {}°C is high", temp.current),
+                message: format!("WARNING: GPU temperature {}°C is high", current),
                 severity: HealthStatus::Warning,
                 timestamp: Local::now(),
-                value: Some(temp.current),
+                value: Some(current),
                 threshold: Some(temp.max_safe),
             });
         }
@@ -372,7 +498,64 @@ impl HealthMonitor {
         }
     }
 
+    /// Temperature climbing while fans don't react: a stuck or blocked fan,
+    /// or one pinned to a manual speed
+    fn check_fan_alerts(&self, gpu: &GpuInfo, temp: &TemperatureMetrics, fans: &FanHealthMetrics, alerts: &mut Vec<HealthAlert>) {
+        if temp.trend_5min < FAN_TEMPERATURE_RISE || fans.flat_fans.is_empty() {
+            return;
+        }
+        
+        let speed = |index: u32| gpu.fans.iter()
+            .find(|fan| fan.index == index)
+            .and_then(|fan| fan.speed)
+            .or(gpu.fan_speed)
+            .unwrap_or(0.0);
+        let flat: Vec<String> = fans.flat_fans.iter()
+            .map(|&index| match gpu.fans.len() {
+                0 | 1 => format!("{:.0}%", speed(index)),
+                _ => format!("#{} {:.0}%", index, speed(index)),
+            })
+            .collect();
+        let manual = gpu.fans.iter()
+            .any(|fan| fans.flat_fans.contains(&fan.index) && fan.policy == Some(FanPolicy::Manual));
+        alerts.push(HealthAlert {
+            alert_type: AlertType::FanIssue,
+            message: format!(
+                "Temperature up {:.1}°C in 5min but fan speed is flat ({}){}",
+                temp.trend_5min,
+                flat.join(", "),
+                if manual { " - manual fan control" } else { " - check for a stuck or blocked fan" }
+            ),
+            severity: HealthStatus::Warning,
+            timestamp: Local::now(),
+            value: temp.current,
+            threshold: Some(FAN_TEMPERATURE_RISE),
+        });
+    }
+
     fn check_power_alerts(&self, power: &PowerMetrics, alerts: &mut Vec<HealthAlert>) {
+        if let (Some(limit), Some(limit_usage)) = (power.power_limit, power.limit_usage) {
+            if limit_usage >= self.thresholds.power_critical {
+                alerts.push(HealthAlert {
+                    alert_type: AlertType::PowerLimit,
+                    message: format!("CRITICAL: Power draw {:.0}W is {:.0}% of the {:.0}W limit", power.current_draw, limit_usage, limit),
+                    severity: HealthStatus::Critical,
+                    timestamp: Local::now(),
+                    value: Some(limit_usage),
+                    threshold: Some(self.thresholds.power_critical),
+                });
+            } else if limit_usage >= self.thresholds.power_warning {
+                alerts.push(HealthAlert {
+                    alert_type: AlertType::PowerLimit,
+                    message: format!("WARNING: Power draw {:.0}W is {:.0}% of the {:.0}W limit", power.current_draw, limit_usage, limit),
+                    severity: HealthStatus::Warning,
+                    timestamp: Local::now(),
+                    value: Some(limit_usage),
+                    threshold: Some(self.thresholds.power_warning),
+                });
+            }
+        }
+
         if power.power_spikes > 10 {
             alerts.push(HealthAlert {
                 alert_type: AlertType::PowerSpike,
@@ -434,6 +617,7 @@ impl AlertType {
             AlertType::TemperatureCritical => "🔥",
             AlertType::ThermalThrottling => "🐌",
             AlertType::PowerSpike => "⚡",
+            AlertType::PowerLimit => "🔌",
             AlertType::MemoryLeakSuspected => "🧠",
             AlertType::ClockInstability => "⏰",
             AlertType::FanIssue => "🌀",
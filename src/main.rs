@@ -1,7 +1,7 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -10,40 +10,158 @@ use ratatui::{
     Terminal,
 };
 use std::io;
-use tokio::time::{interval, Duration};
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
 mod app;
+mod bench;
+mod cluster;
+mod container;
+mod doctor;
+mod energy;
+mod event;
 mod export;
+mod filter;
 mod gpu;
 mod health;
 mod notifications;
+mod nvidia_smi;
+mod priority;
 mod process;
+mod process_tree;
+mod remote;
+mod sampler;
 mod settings;
+mod ssh;
 mod ui;
 mod utils;
 
 use app::App;
+use event::{AppEvent, EventHandler};
+use gpu::GpuSelector;
+use remote::RemoteHost;
+use settings::{ConfigSource, SettingsManager};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Update interval in milliseconds
-    #[arg(short, long, default_value_t = 1000)]
-    interval: u64,
+    /// Update interval in milliseconds [env: GPUTOP_INTERVAL] [default: settings file, then 1000]
+    #[arg(short, long)]
+    interval: Option<u64>,
+    
+    /// Number of history points kept for charts [env: GPUTOP_HISTORY] [default: settings file, then 300]
+    #[arg(long)]
+    history: Option<usize>,
     
-    /// GPU to monitor (0-indexed, default shows all)
+    /// GPU to monitor: index (0-indexed), GPU:MIG index pair (e.g. 0:1) or MIG UUID
     #[arg(short, long)]
-    gpu: Option<usize>,
+    gpu: Option<GpuSelector>,
+    
+    /// Settings file to use instead of ~/.config/gputop/settings.{toml,json}
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    
+    /// Settings profile to apply (defaults to a profile named after the host, if any)
+    #[arg(long)]
+    profile: Option<String>,
+    
+    /// Watch remote agents instead of this machine, e.g. node1,node2:7767
+    #[arg(long, value_name = "HOSTS", value_delimiter = ',')]
+    connect: Vec<String>,
+    
+    /// Watch hosts over ssh without an agent, e.g. alice@node1,node2. Runs
+    /// `gputop snapshot` there, or nvidia-smi if gputop isn't installed
+    #[arg(long, value_name = "DESTINATIONS", value_delimiter = ',')]
+    ssh: Vec<String>,
+    
+    /// Shared secret for agent connections [env: GPUTOP_TOKEN]
+    #[arg(long)]
+    token: Option<String>,
+    
+    /// Fail instead of showing a mock GPU when no GPU backend is available
+    #[arg(long)]
+    no_mock: bool,
     
     /// Enable debug mode
     #[arg(short, long)]
     debug: bool,
+    
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Serve this machine's GPUs, processes and health to `gputop --connect`
+    Agent {
+        /// Address to listen on; use 0.0.0.0:7767 to accept other hosts
+        #[arg(long, default_value_t = SocketAddr::from(([127, 0, 0, 1], remote::DEFAULT_PORT)))]
+        listen: SocketAddr,
+    },
+    
+    /// Show what each GPU backend found and why the others can't be used
+    Doctor,
+    
+    /// Print one sample of this machine and exit; used by `gputop --ssh`
+    Snapshot {
+        #[arg(long, value_enum, default_value_t = SnapshotFormat::Json)]
+        format: SnapshotFormat,
+    },
+    
+    /// Measure process collection time per tick with many processes running
+    Bench {
+        /// Idle processes to start for the measurement
+        #[arg(long, default_value_t = 5000)]
+        processes: usize,
+        
+        /// Ticks to time per collection method
+        #[arg(long, default_value_t = 20)]
+        ticks: usize,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SnapshotFormat {
+    Json,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
     
+    if let Some(Commands::Bench { processes, ticks }) = cli.command {
+        return bench::run(processes, ticks);
+    }
+    if let Some(Commands::Snapshot { format: SnapshotFormat::Json }) = cli.command {
+        return remote::print_snapshot(!cli.no_mock).await;
+    }
+    if let Some(Commands::Doctor) = cli.command {
+        return doctor::run().await;
+    }
+    
+    // Load settings before touching the terminal so config errors print cleanly
+    let settings_manager = SettingsManager::new(ConfigSource {
+        path: cli.config,
+        profile: cli.profile,
+    })?;
+    
+    let token = remote::resolve_token(cli.token);
+    if let Some(Commands::Agent { listen }) = cli.command {
+        let token = token.ok_or_else(|| anyhow::anyhow!("The agent needs a token: pass --token or set {}", remote::TOKEN_ENV))?;
+        let interval = settings::resolve_update_interval(cli.interval, settings_manager.get_settings())?;
+        return remote::run_agent(listen, token, interval, settings_manager, !cli.no_mock).await;
+    }
+    
+    let mut remote_hosts = Vec::new();
+    if !cli.connect.is_empty() {
+        let token = token.ok_or_else(|| anyhow::anyhow!("--connect needs the agents' token: pass --token or set {}", remote::TOKEN_ENV))?;
+        remote_hosts.extend(cli.connect.iter().map(|host| RemoteHost::agent(host, &token)));
+    }
+    remote_hosts.extend(cli.ssh.iter().map(|destination| RemoteHost::ssh(destination)));
+    
+    // Create app before touching the terminal, so startup errors print normally
+    let mut app = App::new(settings_manager, cli.interval, cli.history, cli.gpu, remote_hosts, !cli.no_mock, cli.debug).await?;
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -51,9 +169,6 @@ async fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create app
-    let mut app = App::new(cli.interval, cli.gpu, cli.debug).await?;
-
     // Run the application
     let res = run_app(&mut terminal, &mut app).await;
 
@@ -74,30 +189,60 @@ async fn main() -> Result<()> {
 }
 
 async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
-    let mut update_interval = interval(Duration::from_millis(app.update_interval));
+    let mut events = EventHandler::new(app.update_interval, app.sampler.subscribe());
     
+    // Redraw only after something happened: input, a resize or a new sample
     loop {
         terminal.draw(|f| ui::draw(f, app))?;
-
-        tokio::select! {
-            _ = update_interval.tick() => {
-                app.update().await?;
-            }
-            _ = tokio::time::sleep(Duration::from_millis(50)) => {
-                if event::poll(Duration::from_millis(0))? {
-                    if let Event::Key(key) = event::read()? {
-                        if key.kind == KeyEventKind::Press {
-                            match key.code {
-                                KeyCode::Char('q') => return Ok(()),
-                                KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => return Ok(()),
-                                _ => {
-                                    app.handle_key(key).await?;
-                                }
-                            }
-                        }
+        
+        // Pending SIGTERM escalations run on their own deadline rather than
+        // waiting for the next sampling tick, which can be up to a minute away
+        let next = match app.next_escalation_check() {
+            Some(deadline) => tokio::select! {
+                event = events.next() => event,
+                _ = tokio::time::sleep_until(deadline.into()) => {
+                    app.check_escalations().await?;
+                    continue;
+                }
+            },
+            None => events.next().await,
+        };
+        let Some(mut event) = next else {
+            return Ok(());
+        };
+        
+        // Handle everything already queued before drawing again
+        loop {
+            match event {
+                AppEvent::Key(key) => {
+                    match key.code {
+                        KeyCode::Char('q') if !app.is_text_input_active() => return Ok(()),
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                        _ => app.handle_key(key).await?,
                     }
                 }
+                AppEvent::Mouse(mouse) => {
+                    app.handle_mouse(mouse, terminal.size()?);
+                }
+                AppEvent::Resize => {
+                    terminal.autoresize()?;
+                }
+                AppEvent::Tick => {
+                    app.update().await?;
+                }
+                AppEvent::SampleCompleted => {
+                    app.apply_sample();
+                }
+                AppEvent::InputError(e) => anyhow::bail!("Terminal input failed: {}", e),
+            }
+            
+            match events.try_next() {
+                Some(next) => event = next,
+                None => break,
             }
         }
+        
+        // The interval may have been changed from the TUI
+        events.set_interval(app.update_interval);
     }
 }
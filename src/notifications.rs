@@ -1,11 +1,18 @@
 use std::process::Command;
 use crate::health::{HealthAlert, HealthStatus};
+use crate::settings::NotificationSettings;
 
-pub struct NotificationManager;
+pub struct NotificationManager {
+    settings: NotificationSettings,
+}
 
 impl NotificationManager {
-    pub fn new() -> Self {
-        Self
+    pub fn new(settings: NotificationSettings) -> Self {
+        Self { settings }
+    }
+    
+    pub fn set_settings(&mut self, settings: NotificationSettings) {
+        self.settings = settings;
     }
     
     pub fn send_health_alert(&self, alert: &HealthAlert) {
@@ -23,6 +30,9 @@ impl NotificationManager {
     }
     
     pub fn send_export_success(&self, filename: &str) {
+        if !self.settings.show_export_notifications {
+            return;
+        }
         self.send_toast_notification(
             "GPUTop Export Complete",
             &format!("Data exported to: {}", filename),
@@ -31,6 +41,9 @@ impl NotificationManager {
     }
     
     pub fn send_export_error(&self, error: &str) {
+        if !self.settings.show_export_notifications {
+            return;
+        }
         self.send_toast_notification(
             "GPUTop Export Failed",
             &format!("Export error: {}", error),
@@ -39,6 +52,9 @@ impl NotificationManager {
     }
     
     pub fn send_process_killed(&self, process_name: &str, pid: u32) {
+        if !self.settings.show_process_notifications {
+            return;
+        }
         self.send_toast_notification(
             "GPUTop Process Terminated",
             &format!("Killed process: {} (PID: {})", process_name, pid),
@@ -47,6 +63,10 @@ impl NotificationManager {
     }
     
     fn send_toast_notification(&self, title: &str, message: &str, icon: &str) {
+        if !self.settings.enabled {
+            return;
+        }
+        
         // For Windows, use PowerShell to send toast notifications
         #[cfg(target_os = "windows")]
         {
@@ -114,13 +134,17 @@ pub struct NotificationQueue {
 }
 
 impl NotificationQueue {
-    pub fn new() -> Self {
+    pub fn new(min_interval_seconds: u64) -> Self {
         Self {
             last_notification_time: std::time::Instant::now(),
-            min_interval: std::time::Duration::from_secs(10), // Minimum 10 seconds between notifications
+            min_interval: std::time::Duration::from_secs(min_interval_seconds),
         }
     }
     
+    pub fn set_min_interval(&mut self, min_interval_seconds: u64) {
+        self.min_interval = std::time::Duration::from_secs(min_interval_seconds);
+    }
+    
     pub fn should_send_notification(&mut self) -> bool {
         let now = std::time::Instant::now();
         if now.duration_since(self.last_notification_time) >= self.min_interval {
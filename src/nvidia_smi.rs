@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use crate::gpu::{DeviceProcess, EncoderStats, GpuInfo, GpuVendor, PowerLimits, SensorError, SensorReadings};
+
+/// `--query-gpu` fields, in the order `parse_gpus` reads them
+pub const GPU_QUERY: &str = "index,uuid,name,driver_version,utilization.gpu,memory.used,memory.total,\
+temperature.gpu,fan.speed,power.draw,enforced.power.limit,clocks.gr,clocks.mem,\
+utilization.encoder,utilization.decoder,encoder.stats.sessionCount,encoder.stats.averageFps,encoder.stats.averageLatency";
+
+/// `--query-compute-apps` fields, in the order `parse_apps` reads them
+pub const APP_QUERY: &str = "gpu_uuid,pid,used_memory,process_name";
+
+/// Why nvidia-smi couldn't be queried
+#[derive(Debug, thiserror::Error)]
+pub enum SmiError {
+    #[error("nvidia-smi not found in PATH")]
+    NotFound,
+    #[error("failed to run nvidia-smi: {0}")]
+    Spawn(#[source] std::io::Error),
+    #[error("nvidia-smi failed: {0}")]
+    Failed(String),  // Its own message, e.g. when the driver isn't loaded
+    #[error("Unexpected nvidia-smi {kind} line: {line} ({reason})")]
+    Parse { kind: &'static str, line: String, reason: String },
+    #[error("no GPUs found")]
+    NoGpus,
+}
+
+type Result<T, E = SmiError> = std::result::Result<T, E>;
+
+/// Arguments for `nvidia-smi` that print `query` as bare CSV
+pub fn query_args(kind: &str, query: &str) -> Vec<String> {
+    vec![format!("--query-{}={}", kind, query), "--format=csv,noheader,nounits".to_string()]
+}
+
+/// GPU backend that shells out to `nvidia-smi`, for when gputop can't load
+/// NVML itself: builds without the `nvidia` feature, or containers where
+/// only the nvidia-smi binary is usable
+pub struct NvidiaSmi {
+    gpu_indices: Mutex<HashMap<String, usize>>,  // GPU UUID to index, from the last GPU query
+}
+
+impl NvidiaSmi {
+    /// Succeeds when nvidia-smi runs and lists at least one GPU
+    pub fn probe() -> Result<(Self, Vec<GpuInfo>)> {
+        let smi = Self { gpu_indices: Mutex::new(HashMap::new()) };
+        let gpus = smi.gpus()?;
+        if gpus.is_empty() {
+            return Err(SmiError::NoGpus);
+        }
+        Ok((smi, gpus))
+    }
+
+    pub fn gpus(&self) -> Result<Vec<GpuInfo>> {
+        let gpus = parse_gpus(&run(&query_args("gpu", GPU_QUERY))?)?;
+        let indices = gpus.iter().map(|gpu| (gpu.uuid.clone(), gpu.info.index)).collect();
+        *self.gpu_indices.lock().unwrap_or_else(|e| e.into_inner()) = indices;
+        Ok(gpus.into_iter().map(|gpu| gpu.info).collect())
+    }
+
+    /// Compute processes; nvidia-smi has no CSV query for graphics processes
+    pub fn device_processes(&self) -> Result<Vec<DeviceProcess>> {
+        let apps = parse_apps(&run(&query_args("compute-apps", APP_QUERY))?)?;
+        let indices = self.gpu_indices.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(device_processes(apps, &indices))
+    }
+}
+
+/// Apps on GPUs missing from `indices` (e.g. one that appeared since the
+/// last GPU query) are left out until the next tick
+fn device_processes(apps: Vec<SmiApp>, indices: &HashMap<String, usize>) -> Vec<DeviceProcess> {
+    apps.into_iter()
+        .filter_map(|app| Some(DeviceProcess {
+            pid: app.pid,
+            gpu_index: *indices.get(&app.gpu_uuid)?,
+            gpu_instance_id: None,
+            compute_instance_id: None,
+            memory_used: app.used_memory,
+            gpu_usage: None,
+            encoder_usage: None,
+            decoder_usage: None,
+        }))
+        .collect()
+}
+
+fn run(args: &[String]) -> Result<String> {
+    let output = Command::new("nvidia-smi")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SmiError::NotFound,
+            _ => SmiError::Spawn(e),
+        })?;
+    if !output.status.success() {
+        // nvidia-smi prints its own errors to stdout
+        let text = [output.stdout, output.stderr].concat();
+        let text = String::from_utf8_lossy(&text);
+        let message = text.lines().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+        return Err(SmiError::Failed(message.to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A GPU row of `--query-gpu` output
+#[derive(Debug, Clone)]
+pub struct SmiGpu {
+    pub uuid: String,
+    pub info: GpuInfo,
+}
+
+/// A row of `--query-compute-apps` output
+#[derive(Debug, Clone)]
+pub struct SmiApp {
+    pub gpu_uuid: String,
+    pub pid: u32,
+    pub used_memory: Option<u64>,  // Bytes
+    pub process_name: String,
+}
+
+pub fn parse_gpus(csv: &str) -> Result<Vec<SmiGpu>> {
+    csv.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_gpu(line).map_err(|reason| SmiError::Parse { kind: "GPU", line: line.to_string(), reason }))
+        .collect()
+}
+
+pub fn parse_apps(csv: &str) -> Result<Vec<SmiApp>> {
+    csv.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| !line.contains("No running processes found"))
+        .map(|line| parse_app(line).map_err(|reason| SmiError::Parse { kind: "process", line: line.to_string(), reason }))
+        .collect()
+}
+
+/// Malformed rows are errors; unreadable values only mark their field
+fn parse_gpu(line: &str) -> Result<SmiGpu, String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [index, uuid, name, driver, utilization, memory_used, memory_total, temperature, fan, power, power_limit, gpu_clock, memory_clock,
+        encoder_utilization, decoder_utilization, encoder_sessions, encoder_fps, encoder_latency] = fields[..] else {
+        return Err(format!("expected 18 fields, got {}", fields.len()));
+    };
+
+    let mut readings = SensorReadings::default();
+    let mib = |value: u64| value * 1024 * 1024;
+    let memory_used = readings.read("memory", field::<u64>(memory_used)).map(mib);
+    let memory_total = readings.read("memory", field::<u64>(memory_total)).map(mib);
+    // nvidia-smi has session totals but can't list the sessions
+    let encoder = readings.read("encoder", field(encoder_sessions)).map(|session_count| EncoderStats {
+        session_count,
+        average_fps: number(encoder_fps).unwrap_or(0),
+        average_latency_us: number(encoder_latency).unwrap_or(0),
+        sessions: Vec::new(),
+    });
+    Ok(SmiGpu {
+        uuid: uuid.to_string(),
+        info: GpuInfo {
+            index: index.parse().map_err(|_| format!("invalid GPU index `{}`", index))?,
+            name: name.to_string(),
+            driver_version: driver.to_string(),
+            cuda_version: None,
+            utilization: readings.read("utilization", field(utilization)),
+            memory_used,
+            memory_total,
+            temperature: readings.read("temperature", field(temperature)),
+            fan_speed: readings.read("fan_speed", field(fan)),
+            fans: Vec::new(),
+            power_draw: readings.read("power_draw", field(power)),
+            power_limits: readings.read("power_limits", field(power_limit)).map(|enforced| PowerLimits {
+                enforced,
+                default: None,
+                min: None,
+                max: None,
+            }),
+            total_energy_mj: None,
+            gpu_clock: readings.read("gpu_clock", field(gpu_clock)),
+            memory_clock: readings.read("memory_clock", field(memory_clock)),
+            app_gpu_clock: None,
+            app_memory_clock: None,
+            compute_mode: None,
+            encoder_utilization: readings.read("encoder_utilization", field(encoder_utilization)),
+            decoder_utilization: readings.read("decoder_utilization", field(decoder_utilization)),
+            encoder,
+            throttled: false,
+            vendor: GpuVendor::Nvidia,
+            mig: None,
+            host: None,
+            mock: false,
+            sensor_errors: readings.into_errors(),
+        },
+    })
+}
+
+fn parse_app(line: &str) -> Result<SmiApp, String> {
+    // The process name is last and may itself contain commas
+    let fields: Vec<&str> = line.splitn(4, ',').map(str::trim).collect();
+    let [gpu_uuid, pid, used_memory, process_name] = fields[..] else {
+        return Err(format!("expected 4 fields, got {}", fields.len()));
+    };
+
+    Ok(SmiApp {
+        gpu_uuid: gpu_uuid.to_string(),
+        pid: pid.parse().map_err(|_| format!("invalid PID `{}`", pid))?,
+        used_memory: number::<u64>(used_memory).map(|mib| mib * 1024 * 1024),
+        process_name: process_name.to_string(),
+    })
+}
+
+/// Numeric field, `None` for `[N/A]`, `[Not Supported]` and the like
+fn number<T: std::str::FromStr>(value: &str) -> Option<T> {
+    value.parse().ok()
+}
+
+/// Numeric sensor field. `[N/A]` and `[Not Supported]` mean the device
+/// doesn't report it; other bracketed values like `[Unknown Error]` or
+/// `[GPU requires reset]` are failures.
+fn field<T: std::str::FromStr>(value: &str) -> Result<T, SensorError> {
+    match value {
+        "[N/A]" | "[Not Supported]" => Err(SensorError::NotSupported),
+        _ => value.parse().map_err(|_| SensorError::Failed(value.trim_matches(['[', ']']).to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A100: &str = include_str!("../tests/fixtures/nvidia-smi/query-gpu-a100.csv");
+    const MIG: &str = include_str!("../tests/fixtures/nvidia-smi/query-gpu-mig.csv");
+    const LAPTOP: &str = include_str!("../tests/fixtures/nvidia-smi/query-gpu-laptop.csv");
+    const APPS: &str = include_str!("../tests/fixtures/nvidia-smi/query-compute-apps.csv");
+
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn parses_datacenter_gpus() {
+        let gpus = parse_gpus(A100).unwrap();
+        assert_eq!(gpus.len(), 2);
+
+        let gpu = &gpus[0];
+        assert_eq!(gpu.uuid, "GPU-5b2f3c1e-8a4d-4c9b-9f1e-2d7a6b3c8e01");
+        assert_eq!(gpu.info.index, 0);
+        assert_eq!(gpu.info.name, "NVIDIA A100-SXM4-80GB");
+        assert_eq!(gpu.info.driver_version, "535.129.03");
+        assert_eq!(gpu.info.utilization, Some(98.0));
+        assert_eq!(gpu.info.memory_used, Some(71234 * MIB));
+        assert_eq!(gpu.info.memory_total, Some(81920 * MIB));
+        assert_eq!(gpu.info.temperature, Some(67.0));
+        assert_eq!(gpu.info.fan_speed, None);
+        assert_eq!(gpu.info.power_draw, Some(385.21));
+        assert_eq!(gpu.info.power_limits.as_ref().map(|l| l.enforced), Some(400.0));
+        assert_eq!(gpu.info.gpu_clock, Some(1410));
+        assert_eq!(gpu.info.memory_clock, Some(1593));
+        assert_eq!(gpu.info.vendor, GpuVendor::Nvidia);
+        assert_eq!(gpus[1].info.index, 1);
+    }
+
+    #[test]
+    fn unsupported_fields_are_none() {
+        let gpu = &parse_gpus(LAPTOP).unwrap()[0];
+        assert_eq!(gpu.info.fan_speed, None);
+        assert_eq!(gpu.info.power_draw, None);
+        assert!(gpu.info.power_limits.is_none());
+        assert_eq!(gpu.info.gpu_clock, Some(210));
+        assert_eq!(gpu.info.sensor_error("power_draw"), Some(&SensorError::NotSupported));
+        assert_eq!(gpu.info.sensor_error("power_limits"), Some(&SensorError::NotSupported));
+        assert_eq!(gpu.info.sensor_error("temperature"), None);
+    }
+
+    #[test]
+    fn failed_sensor_only_marks_its_field() {
+        let line = "0, GPU-abc, Tesla T4, 535.129.03, 12, 300, 15360, [Unknown Error], 30, 27.5, 70.00, 585, 5000, 0, 0, 0, 0, 0";
+        let gpu = &parse_gpus(line).unwrap()[0];
+        assert_eq!(gpu.info.temperature, None);
+        assert_eq!(gpu.info.sensor_error("temperature"), Some(&SensorError::Failed("Unknown Error".to_string())));
+        assert_eq!(gpu.info.missing_reason("temperature"), "error");
+        assert_eq!(gpu.info.utilization, Some(12.0));
+        assert_eq!(gpu.info.fan_speed, Some(30.0));
+        assert_eq!(gpu.info.sensor_errors.len(), 1);
+    }
+
+    #[test]
+    fn mig_mode_reports_no_utilization() {
+        let gpu = &parse_gpus(MIG).unwrap()[0];
+        assert_eq!(gpu.info.utilization, None);
+        assert_eq!(gpu.info.missing_reason("utilization"), "unsupported");
+        assert_eq!(gpu.info.memory_used, Some(20512 * MIB));
+    }
+
+    #[test]
+    fn parses_media_engines() {
+        let gpu = &parse_gpus(LAPTOP).unwrap()[0];
+        assert_eq!(gpu.info.encoder_utilization, Some(21.0));
+        assert_eq!(gpu.info.decoder_utilization, Some(4.0));
+        let encoder = gpu.info.encoder.as_ref().unwrap();
+        assert_eq!((encoder.session_count, encoder.average_fps, encoder.average_latency_us), (1, 59, 2140));
+        assert!(encoder.sessions.is_empty());
+
+        let gpu = &parse_gpus(MIG).unwrap()[0];
+        assert_eq!(gpu.info.encoder_utilization, None);
+        assert_eq!(gpu.info.encoder.as_ref().map(|e| e.session_count), Some(0));
+    }
+
+    #[test]
+    fn parses_apps_with_commas_and_missing_memory() {
+        let apps = parse_apps(APPS).unwrap();
+        assert_eq!(apps.len(), 4);
+        assert_eq!(apps[0].pid, 381204);
+        assert_eq!(apps[0].used_memory, Some(70912 * MIB));
+        assert_eq!(apps[1].process_name, "/opt/conda/bin/python -m torch.distributed.run --nproc_per_node=8, train.py");
+        assert_eq!(apps[2].used_memory, None);
+        assert_eq!(apps[2].process_name, "[Insufficient Permissions]");
+    }
+
+    #[test]
+    fn maps_apps_to_gpu_indices() {
+        let gpus = parse_gpus(A100).unwrap();
+        let indices = gpus.iter().map(|gpu| (gpu.uuid.clone(), gpu.info.index)).collect();
+        let processes = device_processes(parse_apps(APPS).unwrap(), &indices);
+
+        let placement: Vec<(u32, usize)> = processes.iter().map(|p| (p.pid, p.gpu_index)).collect();
+        // The app on a GPU missing from the GPU query is dropped
+        assert_eq!(placement, [(381204, 0), (381377, 0), (2214, 1)]);
+    }
+
+    #[test]
+    fn empty_output_has_no_rows() {
+        assert!(parse_gpus("").unwrap().is_empty());
+        assert!(parse_apps("\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_unexpected_columns() {
+        assert!(parse_gpus("0, GPU-abc, Tesla T4").is_err());
+        assert!(parse_apps("GPU-abc, not-a-pid, 12, python").is_err());
+    }
+}
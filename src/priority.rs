@@ -0,0 +1,156 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+pub const NICE_MIN: i32 = -20;
+pub const NICE_MAX: i32 = 19;
+pub const IO_LEVEL_MAX: u8 = 7;
+
+/// Linux I/O scheduling class, as set by `ionice`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum IoClass {
+    None,        // Derived from the nice value
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IoPriority {
+    pub class: IoClass,
+    pub level: u8,  // 0 (highest) to 7, unused for Idle
+}
+
+impl IoPriority {
+    pub fn text(&self) -> String {
+        match self.class {
+            IoClass::None => "none (follows nice)".to_string(),
+            IoClass::Realtime => format!("realtime/{}", self.level),
+            IoClass::BestEffort => format!("best-effort/{}", self.level),
+            IoClass::Idle => "idle".to_string(),
+        }
+    }
+
+    /// Next class offered in the details modal. Realtime needs root and can
+    /// starve the system, so it is never cycled into.
+    pub fn next_class(&self) -> IoPriority {
+        let class = match self.class {
+            IoClass::None | IoClass::Realtime => IoClass::BestEffort,
+            IoClass::BestEffort => IoClass::Idle,
+            IoClass::Idle => IoClass::BestEffort,
+        };
+        IoPriority { class, level: self.level.min(IO_LEVEL_MAX) }
+    }
+}
+
+/// Nice value from `/proc/<pid>/stat`
+pub fn read_nice(pid: u32) -> Option<i32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces; fields after it are well-formed.
+    // Nice is field 19, the 17th after the closing parenthesis.
+    stat.rsplit_once(')')?.1
+        .split_whitespace()
+        .nth(16)?
+        .parse()
+        .ok()
+}
+
+pub fn set_nice(pid: u32, nice: i32) -> Result<()> {
+    let nice = nice.clamp(NICE_MIN, NICE_MAX);
+
+    #[cfg(unix)]
+    {
+        use nix::errno::Errno;
+        use nix::libc;
+
+        Errno::clear();
+        // SAFETY: setpriority only reads its integer arguments
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) };
+        if result == -1 {
+            return Err(match Errno::last() {
+                Errno::EACCES | Errno::EPERM => anyhow::anyhow!(
+                    "Permission denied setting nice {} on PID {} (raising priority needs root or CAP_SYS_NICE)",
+                    nice, pid
+                ),
+                Errno::ESRCH => anyhow::anyhow!("Process {} no longer exists", pid),
+                e => anyhow::anyhow!("Failed to renice PID {}: {}", pid, e),
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        anyhow::bail!("Changing nice of PID {} to {} is only supported on Unix", pid, nice)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod ioprio {
+    // From linux/ioprio.h; libc doesn't export these
+    pub const WHO_PROCESS: i32 = 1;
+    pub const CLASS_SHIFT: i32 = 13;
+    pub const LEVEL_MASK: i32 = (1 << CLASS_SHIFT) - 1;
+}
+
+/// I/O priority via the `ioprio_get` syscall
+pub fn read_io_priority(pid: u32) -> Option<IoPriority> {
+    #[cfg(target_os = "linux")]
+    {
+        use nix::libc;
+
+        // SAFETY: ioprio_get takes two integers and returns an integer
+        let value = unsafe { libc::syscall(libc::SYS_ioprio_get, ioprio::WHO_PROCESS, pid as i32) } as i32;
+        if value < 0 {
+            return None;
+        }
+
+        let class = match value >> ioprio::CLASS_SHIFT {
+            1 => IoClass::Realtime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => IoClass::None,
+        };
+        Some(IoPriority { class, level: (value & ioprio::LEVEL_MASK) as u8 })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+pub fn set_io_priority(pid: u32, priority: IoPriority) -> Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use nix::errno::Errno;
+        use nix::libc;
+
+        let class = match priority.class {
+            IoClass::None => 0,
+            IoClass::Realtime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        };
+        let value = (class << ioprio::CLASS_SHIFT) | i32::from(priority.level.min(IO_LEVEL_MAX));
+
+        // SAFETY: ioprio_set takes three integers and returns an integer
+        let result = unsafe { libc::syscall(libc::SYS_ioprio_set, ioprio::WHO_PROCESS, pid as i32, value) };
+        if result == -1 {
+            return Err(match Errno::last() {
+                Errno::EPERM => anyhow::anyhow!(
+                    "Permission denied setting I/O priority {} on PID {} (needs root or ownership of the process)",
+                    priority.text(), pid
+                ),
+                Errno::ESRCH => anyhow::anyhow!("Process {} no longer exists", pid),
+                e => anyhow::anyhow!("Failed to set I/O priority of PID {}: {}", pid, e),
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        anyhow::bail!("I/O priority of PID {} can't be set to {} on this platform", pid, priority.text())
+    }
+}
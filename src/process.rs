@@ -1,50 +1,336 @@
 use anyhow::Result;
-use sysinfo::{System, SystemExt, ProcessExt, UserExt, PidExt, Process, Pid};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use sysinfo::{System, SystemExt, ProcessExt, ProcessRefreshKind, ProcessStatus, UserExt, PidExt, Process, Pid, Uid};
+use crate::container::{self, ContainerInfo, ContainerRuntime, PodResolver};
+use crate::gpu::DeviceProcess;
+use crate::priority::{self, IoPriority};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GpuProcess {
     pub pid: u32,
     pub user: String,
     pub command: String,
-    pub gpu_usage: f32,
+    pub gpu_usage: Option<f32>,    // SM %, when the driver samples it per process
     pub memory_usage: u64,
-    pub encoder_usage: f32,
-    pub decoder_usage: f32,
-    pub priority: i32,
+    pub encoder_usage: Option<f32>,  // NVENC/NVDEC %, when the driver samples them per process
+    pub decoder_usage: Option<f32>,
+    pub priority: i32,             // Nice value
+    pub io_priority: Option<IoPriority>,
     pub context_id: Option<u32>,
     pub container_id: Option<String>,
+    pub container: Option<ContainerInfo>,
     pub parent_pid: Option<u32>,
+    pub start_time: u64,           // Seconds since epoch; guards against PID reuse
+    pub gpu_index: Option<usize>,  // Device the driver reports the process on, if known
+    pub gpu_instance_id: Option<u32>,
+    pub compute_instance_id: Option<u32>,
+    #[serde(default)]
+    pub host: Option<String>,      // Node the process runs on when watching remote agents
 }
 
+impl GpuProcess {
+    /// Container group the process belongs to, `None` for host processes
+    pub fn container_label(&self) -> Option<String> {
+        self.container.as_ref().map(|c| c.label())
+    }
+}
+
+/// Signals offered when acting on a process
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessSignal {
+    Term,
+    Int,
+    Kill,
+    Stop,
+    Cont,
+}
+
+impl ProcessSignal {
+    pub const ALL: [ProcessSignal; 5] = [
+        ProcessSignal::Term,
+        ProcessSignal::Int,
+        ProcessSignal::Kill,
+        ProcessSignal::Stop,
+        ProcessSignal::Cont,
+    ];
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcessSignal::Term => "SIGTERM",
+            ProcessSignal::Int => "SIGINT",
+            ProcessSignal::Kill => "SIGKILL",
+            ProcessSignal::Stop => "SIGSTOP",
+            ProcessSignal::Cont => "SIGCONT",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            ProcessSignal::Term => "Ask the process to terminate",
+            ProcessSignal::Int => "Interrupt, like Ctrl+C",
+            ProcessSignal::Kill => "Kill immediately, no cleanup",
+            ProcessSignal::Stop => "Pause the process",
+            ProcessSignal::Cont => "Resume a paused process",
+        }
+    }
+    
+    #[cfg(unix)]
+    fn to_nix(self) -> nix::sys::signal::Signal {
+        use nix::sys::signal::Signal;
+        
+        match self {
+            ProcessSignal::Term => Signal::SIGTERM,
+            ProcessSignal::Int => Signal::SIGINT,
+            ProcessSignal::Kill => Signal::SIGKILL,
+            ProcessSignal::Stop => Signal::SIGSTOP,
+            ProcessSignal::Cont => Signal::SIGCONT,
+        }
+    }
+    
+    #[cfg(not(unix))]
+    fn to_sysinfo(self) -> sysinfo::Signal {
+        match self {
+            ProcessSignal::Term => sysinfo::Signal::Term,
+            ProcessSignal::Int => sysinfo::Signal::Interrupt,
+            ProcessSignal::Kill => sysinfo::Signal::Kill,
+            ProcessSignal::Stop => sysinfo::Signal::Stop,
+            ProcessSignal::Cont => sysinfo::Signal::Continue,
+        }
+    }
+}
+
+/// How often the whole process list is re-read to pick up new and exited
+/// processes. In between only the PIDs of interest are refreshed.
+const DISCOVERY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Minimum time between re-reads of the user database for unknown UIDs
+const USER_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct ProcessManager {
     system: System,
+    containers: HashMap<u32, Option<ContainerInfo>>,  // cgroup attribution cache by PID
+    pod_resolver: PodResolver,
+    user_names: HashMap<Uid, String>,
+    users_refreshed: Option<Instant>,
+    last_discovery: Option<Instant>,
+    candidates: Vec<u32>,  // Heuristic matches refreshed between discoveries
+    cpu: CpuTracker,
 }
 
 impl ProcessManager {
     pub fn new() -> Self {
         Self {
-            system: System::new_all(),
+            system: System::new(),
+            containers: HashMap::new(),
+            pod_resolver: PodResolver::new(PathBuf::from("/var/lib/kubelet/pods")),
+            user_names: HashMap::new(),
+            users_refreshed: None,
+            last_discovery: None,
+            candidates: Vec::new(),
+            cpu: CpuTracker::new(),
         }
     }
 
-    pub async fn get_gpu_processes(&mut self) -> Result<Vec<GpuProcess>> {
-        self.system.refresh_all();
+    pub async fn get_gpu_processes(&mut self, device_processes: &[DeviceProcess]) -> Result<Vec<GpuProcess>> {
+        let discover = self.last_discovery.is_none_or(|at| at.elapsed() >= DISCOVERY_INTERVAL);
+        if discover {
+            self.system.refresh_processes_specifics(refresh_kind());
+            self.last_discovery = Some(Instant::now());
+            self.cpu.forget_exited(&self.system);
+        }
         
         let mut processes = Vec::new();
         
-        // Get real GPU processes from system
-        processes.extend(self.get_real_processes());
-        
-        // If no real processes found, show a few mock ones for demo
-        if processes.is_empty() {
-            processes.extend(self.get_mock_processes());
+        // Prefer processes the driver reports; fall back to name heuristics
+        if device_processes.is_empty() {
+            let pids: Vec<u32> = if discover {
+                self.system.processes().keys().map(|pid| pid.as_u32()).collect()
+            } else {
+                self.refresh_pids(&self.candidates.clone());
+                self.candidates.clone()
+            };
+            self.update_user_names(&pids);
+            processes.extend(self.get_real_processes(&pids));
+            self.candidates = processes.iter().map(|p| p.pid).collect();
+        } else {
+            let pids: Vec<u32> = device_processes.iter().map(|p| p.pid).collect();
+            if !discover {
+                self.refresh_pids(&pids);
+            }
+            self.update_user_names(&pids);
+            processes.extend(self.get_device_backed_processes(device_processes));
         }
+        self.attribute_containers(&mut processes);
+        Self::read_priorities(&mut processes);
         
         Ok(processes)
     }
 
+    /// Re-reads just these PIDs. New ones are added, exited ones stay listed
+    /// until the next discovery but are skipped since their refresh fails.
+    fn refresh_pids(&mut self, pids: &[u32]) {
+        for &pid in pids {
+            let sys_pid = Pid::from(pid as usize);
+            if !self.system.refresh_process_specifics(sys_pid, refresh_kind()) {
+                self.cpu.forget(pid);
+            }
+        }
+    }
+
+    /// Non-GPU parents shared by several GPU processes, e.g. a `torchrun`
+    /// launcher and its worker ranks. Used to give the tree view its roots.
+    /// Parents are taken from the last discovery; they rarely change.
+    pub fn get_launchers(&mut self, processes: &[GpuProcess]) -> Vec<GpuProcess> {
+        let pids: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+        let mut child_counts: HashMap<u32, usize> = HashMap::new();
+        for parent in processes.iter().filter_map(|p| p.parent_pid) {
+            if !pids.contains(&parent) {
+                *child_counts.entry(parent).or_insert(0) += 1;
+            }
+        }
+        
+        // PID 1 is the parent of every daemon and says nothing about the job
+        let parents: Vec<u32> = child_counts.into_iter()
+            .filter(|(pid, count)| *count >= 2 && *pid > 1)
+            .map(|(pid, _)| pid)
+            .collect();
+        self.update_user_names(&parents);
+        
+        let mut launchers: Vec<GpuProcess> = parents.iter()
+            .filter_map(|pid| self.system.process(Pid::from(*pid as usize)))
+            .map(|process| GpuProcess {
+                pid: process.pid().as_u32(),
+                user: self.user_name(process),
+                command: Self::display_command(process),
+                gpu_usage: None,
+                memory_usage: 0,
+                encoder_usage: None,
+                decoder_usage: None,
+                priority: 0,
+                io_priority: None,
+                context_id: None,
+                container_id: None,
+                container: None,
+                parent_pid: process.parent().map(|p| p.as_u32()),
+                start_time: process.start_time(),
+                gpu_index: None,
+                gpu_instance_id: None,
+                compute_instance_id: None,
+                host: None,
+            })
+            .collect();
+        
+        self.attribute_containers(&mut launchers);
+        Self::read_priorities(&mut launchers);
+        launchers
+    }
+
+    /// Re-reads the user database when one of `pids` runs as a UID that
+    /// isn't cached yet, at most once per USER_REFRESH_INTERVAL
+    fn update_user_names(&mut self, pids: &[u32]) {
+        let unknown = pids.iter()
+            .filter_map(|pid| self.system.process(Pid::from(*pid as usize)))
+            .filter_map(|process| process.user_id())
+            .any(|uid| !self.user_names.contains_key(uid));
+        let due = self.users_refreshed.is_none_or(|at| at.elapsed() >= USER_REFRESH_INTERVAL);
+        
+        if unknown && due {
+            self.system.refresh_users_list();
+            self.user_names = self.system.users().iter()
+                .map(|user| (user.id().clone(), user.name().to_string()))
+                .collect();
+            self.users_refreshed = Some(Instant::now());
+        }
+    }
+
+    fn user_name(&self, process: &Process) -> String {
+        process.user_id()
+            .and_then(|uid| self.user_names.get(uid))
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    fn display_command(process: &Process) -> String {
+        process.exe().file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(process.name())
+            .to_string()
+    }
+
+    fn read_priorities(processes: &mut [GpuProcess]) {
+        for process in processes.iter_mut() {
+            process.priority = priority::read_nice(process.pid).unwrap_or(0);
+            process.io_priority = priority::read_io_priority(process.pid);
+        }
+    }
+
+    fn attribute_containers(&mut self, processes: &mut [GpuProcess]) {
+        let system = &self.system;
+        self.containers.retain(|pid, _| system.process(Pid::from(*pid as usize)).is_some());
+        
+        for process in processes.iter_mut() {
+            let pid = process.pid;
+            let container = match self.containers.get(&pid) {
+                Some(container) => container.clone(),
+                None => {
+                    let mut container = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()
+                        .and_then(|cgroup| container::parse_cgroup(&cgroup));
+                    if let Some(pod) = container.as_mut().and_then(|c| c.pod.as_mut()) {
+                        self.pod_resolver.resolve(pid, pod);
+                    }
+                    // Pods the kubelet hasn't fully described yet are looked up again next tick
+                    let complete = container.as_ref()
+                        .and_then(|c| c.pod.as_ref())
+                        .is_none_or(|pod| pod.name.is_some() && pod.namespace.is_some());
+                    if complete {
+                        self.containers.insert(pid, container.clone());
+                    }
+                    container
+                }
+            };
+            
+            process.container_id = container.as_ref().map(|c| c.id.clone());
+            process.container = container;
+        }
+    }
+
+    fn get_device_backed_processes(&mut self, device_processes: &[DeviceProcess]) -> Vec<GpuProcess> {
+        let mut gpu_processes = Vec::new();
+        
+        for device_process in device_processes {
+            let Some(process) = self.system.process(Pid::from(device_process.pid as usize)) else {
+                continue;
+            };
+            
+            gpu_processes.push(GpuProcess {
+                pid: device_process.pid,
+                user: self.user_name(process),
+                command: Self::display_command(process),
+                gpu_usage: device_process.gpu_usage,
+                memory_usage: device_process.memory_used.unwrap_or(0),
+                encoder_usage: device_process.encoder_usage,
+                decoder_usage: device_process.decoder_usage,
+                priority: 0,
+                io_priority: None,
+                context_id: None,
+                container_id: None,
+                container: None,
+                parent_pid: process.parent().map(|p| p.as_u32()),
+                start_time: process.start_time(),
+                gpu_index: Some(device_process.gpu_index),
+                gpu_instance_id: device_process.gpu_instance_id,
+                compute_instance_id: device_process.compute_instance_id,
+                host: None,
+            });
+        }
+        
+        gpu_processes
+    }
 
-    fn get_real_processes(&self) -> Vec<GpuProcess> {
+    fn get_real_processes(&mut self, pids: &[u32]) -> Vec<GpuProcess> {
         let mut gpu_processes = Vec::new();
         
         // Common GPU-intensive process names
@@ -54,23 +340,24 @@ impl ProcessManager {
             "firefox", "discord", "steam", "game", "vlc", "mpv", "handbrake"
         ];
         
-        for (pid, process) in self.system.processes() {
+        for pid in pids {
+            let Some(process) = self.system.process(Pid::from(*pid as usize)) else {
+                continue;
+            };
+            let cpu_usage = self.cpu.usage(process);
             let process_name = process.name().to_lowercase();
             let exe_path = process.exe().to_string_lossy().to_lowercase();
             
             // Check if this process might be using GPU
             let is_gpu_process = gpu_intensive_names.iter().any(|&name| {
                 process_name.contains(name) || exe_path.contains(name)
-            }) || process.cpu_usage() > 15.0; // High CPU usage might indicate GPU usage
+            }) || cpu_usage > 15.0; // High CPU usage might indicate GPU usage
             
             if is_gpu_process {
-                let user_name = process.user_id()
-                    .and_then(|uid| self.system.get_user_by_id(uid))
-                    .map(|user| user.name().to_string())
-                    .unwrap_or_else(|| "unknown".to_string());
+                let user_name = self.user_name(process);
                 
                 // Estimate GPU usage based on CPU usage (rough approximation)
-                let estimated_gpu_usage = (process.cpu_usage() * 0.7).min(100.0);
+                let estimated_gpu_usage = (cpu_usage * 0.7).min(100.0);
                 let memory_bytes = process.memory() * 1024; // Convert KB to bytes
                 
                 // Extract process name with extension from executable path - display ONLY the name
@@ -81,21 +368,24 @@ impl ProcessManager {
                     .to_string();
                 
                 gpu_processes.push(GpuProcess {
-                    pid: pid.as_u32(),
+                    pid: *pid,
                     user: user_name,
                     command: display_command,
-                    gpu_usage: estimated_gpu_usage,
+                    gpu_usage: Some(estimated_gpu_usage),
                     memory_usage: memory_bytes,
-                    encoder_usage: if process_name.contains("ffmpeg") || process_name.contains("obs") { 
-                        (process.cpu_usage() * 0.4).min(100.0) 
-                    } else { 0.0 },
-                    decoder_usage: if process_name.contains("chrome") || process_name.contains("firefox") {
-                        (process.cpu_usage() * 0.2).min(100.0)
-                    } else { 0.0 },
+                    encoder_usage: None,
+                    decoder_usage: None,
                     priority: 0,
+                    io_priority: None,
                     context_id: None,
                     container_id: None,
+                    container: None,
                     parent_pid: process.parent().map(|p| p.as_u32()),
+                    start_time: process.start_time(),
+                    gpu_index: None,
+                    gpu_instance_id: None,
+                    compute_instance_id: None,
+                    host: None,
                 });
             }
         }
@@ -109,71 +399,214 @@ impl ProcessManager {
         gpu_processes
     }
     
-    fn get_mock_processes(&self) -> Vec<GpuProcess> {
+    /// Demo processes shown alongside the mock GPU
+    pub fn mock_processes() -> Vec<GpuProcess> {
         vec![
             GpuProcess {
                 pid: 1234,
                 user: "user1".to_string(),
                 command: "python.exe".to_string(),
-                gpu_usage: 85.2,
+                gpu_usage: Some(85.2),
                 memory_usage: 3 * 1024 * 1024 * 1024, // 3GB
-                encoder_usage: 0.0,
-                decoder_usage: 0.0,
+                encoder_usage: Some(0.0),
+                decoder_usage: Some(0.0),
                 priority: 0,
+                io_priority: None,
                 context_id: Some(1),
                 container_id: None,
+                container: None,
                 parent_pid: Some(1000),
+                start_time: 0,
+                gpu_index: Some(0),
+                gpu_instance_id: None,
+                compute_instance_id: None,
+                host: None,
             },
             GpuProcess {
                 pid: 5678,
                 user: "user2".to_string(),
                 command: "blender.exe".to_string(),
-                gpu_usage: 65.8,
+                gpu_usage: Some(65.8),
                 memory_usage: 1536 * 1024 * 1024, // 1.5GB
-                encoder_usage: 0.0,
-                decoder_usage: 0.0,
+                encoder_usage: Some(0.0),
+                decoder_usage: Some(0.0),
                 priority: 0,
+                io_priority: None,
                 context_id: Some(2),
                 container_id: Some("docker-container-123".to_string()),
+                container: Some(ContainerInfo {
+                    runtime: ContainerRuntime::Docker,
+                    id: "docker-container-123".to_string(),
+                    pod: None,
+                }),
                 parent_pid: Some(2000),
+                start_time: 0,
+                gpu_index: Some(0),
+                gpu_instance_id: None,
+                compute_instance_id: None,
+                host: None,
             },
             GpuProcess {
                 pid: 9012,
                 user: "root".to_string(),
                 command: "ffmpeg.exe".to_string(),
-                gpu_usage: 25.3,
+                gpu_usage: Some(25.3),
                 memory_usage: 512 * 1024 * 1024, // 512MB
-                encoder_usage: 45.0,
-                decoder_usage: 0.0,
+                encoder_usage: Some(45.0),
+                decoder_usage: Some(0.0),
                 priority: -10,
+                io_priority: None,
                 context_id: Some(3),
                 container_id: None,
+                container: None,
                 parent_pid: Some(1),
+                start_time: 0,
+                gpu_index: Some(0),
+                gpu_instance_id: None,
+                compute_instance_id: None,
+                host: None,
             },
         ]
     }
 
-    pub fn kill_process(&mut self, pid: u32) -> Result<()> {
-        self.system.refresh_processes();
+    /// Sends `signal` to `pid` after checking it is still the process that
+    /// was listed, so a recycled PID is never signalled
+    pub fn send_signal(&mut self, pid: u32, start_time: u64, signal: ProcessSignal) -> Result<()> {
+        self.ensure_same_process(pid, start_time)?;
         
-        if let Some(process) = self.system.process(Pid::from(pid as usize)) {
-            if process.kill() {
-                Ok(())
-            } else {
-                anyhow::bail!("Failed to kill process with PID {}", pid)
+        #[cfg(unix)]
+        {
+            use nix::errno::Errno;
+            
+            nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid as i32), signal.to_nix()).map_err(|e| match e {
+                Errno::EPERM => anyhow::anyhow!("Permission denied sending {} to PID {} (run as its owner or root)", signal.name(), pid),
+                Errno::ESRCH => anyhow::anyhow!("Process {} exited before {} was sent", pid, signal.name()),
+                e => anyhow::anyhow!("Failed to send {} to PID {}: {}", signal.name(), pid, e),
+            })
+        }
+        
+        #[cfg(not(unix))]
+        {
+            let process = self.system.process(Pid::from(pid as usize))
+                .ok_or_else(|| anyhow::anyhow!("Process with PID {} not found", pid))?;
+            match process.kill_with(signal.to_sysinfo()) {
+                Some(true) => Ok(()),
+                Some(false) => anyhow::bail!("Failed to send {} to PID {}", signal.name(), pid),
+                None => anyhow::bail!("{} is not supported on this platform", signal.name()),
             }
-        } else {
-            anyhow::bail!("Process with PID {} not found", pid)
         }
     }
 
+    pub fn renice(&mut self, pid: u32, start_time: u64, nice: i32) -> Result<()> {
+        self.ensure_same_process(pid, start_time)?;
+        priority::set_nice(pid, nice)
+    }
+
+    pub fn set_io_priority(&mut self, pid: u32, start_time: u64, io_priority: IoPriority) -> Result<()> {
+        self.ensure_same_process(pid, start_time)?;
+        priority::set_io_priority(pid, io_priority)
+    }
+
+    fn ensure_same_process(&mut self, pid: u32, start_time: u64) -> Result<()> {
+        if !self.is_running(pid, start_time) {
+            anyhow::bail!("PID {} no longer refers to the selected process (it exited or the PID was reused)", pid);
+        }
+        Ok(())
+    }
+
+    /// Whether `pid` is alive and started at `start_time`. Zombies count as exited.
+    pub fn is_running(&mut self, pid: u32, start_time: u64) -> bool {
+        let sys_pid = Pid::from(pid as usize);
+        self.system.refresh_process(sys_pid)
+            && self.system.process(sys_pid).is_some_and(|p| {
+                p.start_time() == start_time && p.status() != ProcessStatus::Zombie
+            })
+    }
+
     pub fn get_process_name(&mut self, pid: u32) -> Option<String> {
-        self.system.refresh_processes();
+        let sys_pid = Pid::from(pid as usize);
+        self.system.refresh_process_specifics(sys_pid, refresh_kind());
         
-        if let Some(process) = self.system.process(Pid::from(pid as usize)) {
-            Some(process.name().to_string())
-        } else {
-            None
+        self.system.process(sys_pid).map(|process| process.name().to_string())
+    }
+}
+
+/// What discovery and targeted refreshes read. Disk usage is never needed.
+/// On Linux CPU time is read by `CpuTracker`, since sysinfo's per-PID CPU
+/// refresh compares against the time since the previous single-PID refresh.
+fn refresh_kind() -> ProcessRefreshKind {
+    let kind = ProcessRefreshKind::new().with_user();
+    if cfg!(target_os = "linux") { kind } else { kind.with_cpu() }
+}
+
+/// Per-process CPU usage from the CPU time consumed between two samples,
+/// in percent of one core like sysinfo reports it
+struct CpuTracker {
+    #[cfg(target_os = "linux")]
+    previous: HashMap<u32, (u64, Instant)>,  // CPU ticks and when they were read
+    #[cfg(target_os = "linux")]
+    ticks_per_second: f32,
+}
+
+impl CpuTracker {
+    fn new() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            previous: HashMap::new(),
+            // SAFETY: sysconf only reads its integer argument
+            #[cfg(target_os = "linux")]
+            ticks_per_second: match unsafe { nix::libc::sysconf(nix::libc::_SC_CLK_TCK) } {
+                ticks if ticks > 0 => ticks as f32,
+                _ => 100.0,
+            },
         }
     }
+
+    /// Usage since the previous call for this process; 0 on the first call
+    fn usage(&mut self, process: &Process) -> f32 {
+        #[cfg(target_os = "linux")]
+        {
+            let pid = process.pid().as_u32();
+            let Some(ticks) = read_cpu_ticks(pid) else { return 0.0 };
+            let now = Instant::now();
+            
+            match self.previous.insert(pid, (ticks, now)) {
+                Some((before, at)) if ticks >= before => {
+                    let seconds = now.duration_since(at).as_secs_f32();
+                    if seconds > 0.0 {
+                        (ticks - before) as f32 / self.ticks_per_second / seconds * 100.0
+                    } else {
+                        0.0
+                    }
+                }
+                _ => 0.0,
+            }
+        }
+        
+        #[cfg(not(target_os = "linux"))]
+        {
+            process.cpu_usage()
+        }
+    }
+
+    fn forget(&mut self, _pid: u32) {
+        #[cfg(target_os = "linux")]
+        self.previous.remove(&_pid);
+    }
+
+    fn forget_exited(&mut self, _system: &System) {
+        #[cfg(target_os = "linux")]
+        self.previous.retain(|pid, _| _system.process(Pid::from(*pid as usize)).is_some());
+    }
+}
+
+/// User plus system time from `/proc/<pid>/stat`, in clock ticks
+#[cfg(target_os = "linux")]
+fn read_cpu_ticks(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // utime and stime are fields 14 and 15, the 12th and 13th after the command
+    let mut fields = stat.rsplit_once(')')?.1.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
 }
@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+use crate::process::GpuProcess;
+
+/// One line of the process table, either flat or as part of the tree view
+#[derive(Debug, Clone)]
+pub struct ProcessRow<'a> {
+    pub process: &'a GpuProcess,
+    pub prefix: String,        // Box-drawing guides, e.g. "│  ├─ "
+    pub has_children: bool,
+    pub collapsed: bool,
+    pub total_gpu_usage: Option<f32>,  // Own usage plus all descendants, None if none is sampled
+    pub total_memory: u64,
+}
+
+impl<'a> ProcessRow<'a> {
+    pub fn flat(process: &'a GpuProcess) -> Self {
+        Self {
+            process,
+            prefix: String::new(),
+            has_children: false,
+            collapsed: false,
+            total_gpu_usage: process.gpu_usage,
+            total_memory: process.memory_usage,
+        }
+    }
+}
+
+/// Arranges processes by `parent_pid`, keeping the input order among
+/// siblings. Children of collapsed PIDs are omitted but still counted in
+/// their ancestors' totals.
+pub fn build<'a>(processes: &[&'a GpuProcess], collapsed: &HashSet<u32>) -> Vec<ProcessRow<'a>> {
+    let pids: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+    let mut children: HashMap<u32, Vec<&'a GpuProcess>> = HashMap::new();
+    let mut roots = Vec::new();
+    
+    for &process in processes {
+        match process.parent_pid.filter(|parent| *parent != process.pid && pids.contains(parent)) {
+            Some(parent) => children.entry(parent).or_default().push(process),
+            None => roots.push(process),
+        }
+    }
+    
+    let mut tree = TreeBuilder {
+        children: &children,
+        collapsed,
+        visited: HashSet::new(),
+        rows: Vec::with_capacity(processes.len()),
+    };
+    
+    let root_count = roots.len();
+    for (i, root) in roots.into_iter().enumerate() {
+        tree.visit(root, 0, "", i + 1 == root_count);
+    }
+    
+    // Parent cycles (possible after PID reuse) leave nodes unreachable from a root
+    for &process in processes {
+        if !tree.visited.contains(&process.pid) {
+            tree.visit(process, 0, "", true);
+        }
+    }
+    
+    tree.rows
+}
+
+struct TreeBuilder<'a, 'b> {
+    children: &'b HashMap<u32, Vec<&'a GpuProcess>>,
+    collapsed: &'b HashSet<u32>,
+    visited: HashSet<u32>,
+    rows: Vec<ProcessRow<'a>>,
+}
+
+impl<'a, 'b> TreeBuilder<'a, 'b> {
+    fn visit(&mut self, process: &'a GpuProcess, depth: usize, guides: &str, is_last: bool) {
+        if !self.visited.insert(process.pid) {
+            return;
+        }
+        
+        let kids = self.children.get(&process.pid).map(Vec::as_slice).unwrap_or(&[]);
+        let collapsed = self.collapsed.contains(&process.pid);
+        let (total_gpu_usage, total_memory) = self.totals(process, &mut HashSet::new());
+        
+        let prefix = if depth == 0 {
+            String::new()
+        } else {
+            format!("{}{}", guides, if is_last { "└─ " } else { "├─ " })
+        };
+        
+        self.rows.push(ProcessRow {
+            process,
+            prefix,
+            has_children: !kids.is_empty(),
+            collapsed,
+            total_gpu_usage,
+            total_memory,
+        });
+        
+        if collapsed {
+            // Still mark the subtree as placed so it isn't re-rooted later
+            self.mark_visited(process);
+            return;
+        }
+        
+        let child_guides = if depth == 0 {
+            String::new()
+        } else {
+            format!("{}{}", guides, if is_last { "   " } else { "│  " })
+        };
+        for (i, child) in kids.iter().enumerate() {
+            self.visit(child, depth + 1, &child_guides, i + 1 == kids.len());
+        }
+    }
+
+    fn totals(&self, process: &GpuProcess, seen: &mut HashSet<u32>) -> (Option<f32>, u64) {
+        let mut totals = (process.gpu_usage, process.memory_usage);
+        if !seen.insert(process.pid) {
+            return (None, 0);
+        }
+        
+        for child in self.children.get(&process.pid).into_iter().flatten() {
+            let (gpu_usage, memory) = self.totals(child, seen);
+            totals.0 = sum_usage(totals.0, gpu_usage);
+            totals.1 += memory;
+        }
+        totals
+    }
+
+    fn mark_visited(&mut self, process: &GpuProcess) {
+        for child in self.children.get(&process.pid).into_iter().flatten() {
+            if self.visited.insert(child.pid) {
+                self.mark_visited(child);
+            }
+        }
+    }
+}
+
+/// Sum of the sampled usages, None when neither is sampled
+pub fn sum_usage(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (None, None) => None,
+        _ => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+    }
+}
@@ -0,0 +1,462 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+use sysinfo::{System, SystemExt};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use crate::gpu::{GpuInfo, GpuManager};
+use crate::health::{GpuHealthMetrics, HealthMonitor};
+use crate::process::{GpuProcess, ProcessManager};
+use crate::sampler::{Sampler, Snapshot, Source};
+use crate::settings::{HealthThresholds, SettingsManager};
+use crate::ssh::SshHost;
+
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 7767;
+pub const TOKEN_ENV: &str = "GPUTOP_TOKEN";
+
+/// Longest request line the agent accepts
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+/// Longest response line the client accepts; a busy node's snapshot is a few hundred KB
+const MAX_RESPONSE_BYTES: u64 = 64 * 1024 * 1024;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Client to agent. The protocol is newline-delimited JSON over TCP, one
+/// object per line: the client opens with `hello` carrying the shared token,
+/// then sends `snapshot` requests. See README "Remote Monitoring".
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Hello { version: u32, token: String },
+    Snapshot,
+}
+
+/// Agent to client. `hello` is answered with `welcome`, or with `error`
+/// after which the agent closes the connection.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Welcome { version: u32, hostname: String },
+    Snapshot { snapshot: HostSnapshot },
+    Error { message: String },
+}
+
+/// Everything an agent knows about its node at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostSnapshot {
+    pub hostname: String,
+    pub taken_at: DateTime<Local>,
+    pub gpus: Vec<GpuInfo>,
+    pub processes: Vec<GpuProcess>,
+    pub launchers: Vec<GpuProcess>,
+    #[serde(default)]
+    pub health: HashMap<String, GpuHealthMetrics>,  // By GPU display id; empty from `gputop snapshot`
+}
+
+impl HostSnapshot {
+    fn new(hostname: &str, sample: &Snapshot, health: HashMap<String, GpuHealthMetrics>) -> Self {
+        Self {
+            hostname: hostname.to_string(),
+            taken_at: sample.taken_at,
+            gpus: sample.gpus.clone(),
+            processes: sample.processes.clone(),
+            launchers: sample.launchers.clone(),
+            health,
+        }
+    }
+}
+
+/// Adds the default port to `host` unless it has one. IPv6 addresses
+/// need brackets: `[::1]` or `[::1]:7767`.
+pub fn with_default_port(host: &str) -> String {
+    let has_port = host.rsplit_once(':')
+        .is_some_and(|(name, port)| port.parse::<u16>().is_ok() && (!name.contains(':') || name.ends_with(']')));
+    if has_port {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, DEFAULT_PORT)
+    }
+}
+
+/// Token from `--token`, then GPUTOP_TOKEN
+pub fn resolve_token(cli: Option<String>) -> Option<String> {
+    cli.or_else(|| std::env::var(TOKEN_ENV).ok())
+        .filter(|token| !token.is_empty())
+}
+
+/// Constant-time comparison, so response timing doesn't leak the token
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn local_hostname() -> String {
+    System::new().host_name().unwrap_or_else(|| "unknown".to_string())
+}
+
+async fn start_local_sampler(allow_mock: bool) -> Result<Sampler> {
+    let gpu_manager = Arc::new(GpuManager::new(allow_mock).await?);
+    Sampler::start(Source::Local { gpu_manager, process_manager: Box::new(ProcessManager::new()) })
+}
+
+/// `gputop snapshot`: one sample of this node, printed as JSON for
+/// `gputop --ssh` on another machine. Health needs history, so the
+/// watching side computes it.
+pub async fn print_snapshot(allow_mock: bool) -> Result<()> {
+    let sampler = start_local_sampler(allow_mock).await?;
+    let snapshot = HostSnapshot::new(&local_hostname(), &sampler.latest(), HashMap::new());
+    println!("{}", serde_json::to_string(&snapshot)?);
+    Ok(())
+}
+
+/// `gputop agent`: samples this node and serves snapshots until killed.
+/// Config file changes are picked up on the next sampling tick.
+pub async fn run_agent(
+    listen: SocketAddr,
+    token: String,
+    interval_ms: u64,
+    mut settings_manager: SettingsManager,
+    allow_mock: bool,
+) -> Result<()> {
+    let hostname = local_hostname();
+    let sampler = start_local_sampler(allow_mock).await?;
+
+    // Health history is kept per GPU, since the agent serves all of them
+    let mut monitors: HashMap<String, HealthMonitor> = HashMap::new();
+    let mut thresholds = settings_manager.get_settings().health_thresholds.clone();
+    let mut applied_interval_ms = settings_manager.get_settings().update_interval_ms;
+
+    let (publisher, latest) = watch::channel(host_snapshot(&hostname, &sampler, &mut monitors, &thresholds));
+    let mut samples = sampler.subscribe();
+    tokio::spawn(async move {
+        let mut timer = sampling_timer(interval_ms);
+        loop {
+            timer.tick().await;
+            match settings_manager.reload_if_changed() {
+                Some(Ok(())) => {
+                    let settings = settings_manager.get_settings();
+                    thresholds = settings.health_thresholds.clone();
+                    for monitor in monitors.values_mut() {
+                        monitor.set_thresholds(thresholds.clone());
+                    }
+                    // Like the TUI, a changed interval in the file overrides --interval
+                    if settings.update_interval_ms != applied_interval_ms {
+                        applied_interval_ms = settings.update_interval_ms;
+                        timer = sampling_timer(applied_interval_ms);
+                        timer.tick().await;
+                    }
+                    eprintln!("Settings reloaded from config file");
+                }
+                Some(Err(e)) => eprintln!("Settings reload failed, keeping current settings: {:#}", e),
+                None => {}
+            }
+            
+            sampler.request();
+            if samples.changed().await.is_err() {
+                break;
+            }
+            let _ = publisher.send(host_snapshot(&hostname, &sampler, &mut monitors, &thresholds));
+        }
+    });
+
+    let listener = TcpListener::bind(listen).await
+        .with_context(|| format!("Failed to listen on {}", listen))?;
+    eprintln!("gputop agent serving {} on {}", latest.borrow().hostname, listen);
+
+    let token = Arc::new(token);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let token = token.clone();
+        let latest = latest.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve_client(stream, &token, latest).await {
+                eprintln!("{}: {:#}", peer, e);
+            }
+        });
+    }
+}
+
+fn sampling_timer(interval_ms: u64) -> tokio::time::Interval {
+    let mut timer = tokio::time::interval(Duration::from_millis(interval_ms));
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    timer
+}
+
+/// The sampler's latest sample with each GPU's health, as served to clients
+fn host_snapshot(
+    hostname: &str,
+    sampler: &Sampler,
+    monitors: &mut HashMap<String, HealthMonitor>,
+    thresholds: &HealthThresholds,
+) -> Arc<HostSnapshot> {
+    let sample = sampler.latest();
+    let health = sample.gpus.iter()
+        .map(|gpu| {
+            let monitor = monitors.entry(gpu.display_id())
+                .or_insert_with(|| HealthMonitor::new(thresholds.clone()));
+            (gpu.display_id(), monitor.update_metrics(gpu))
+        })
+        .collect();
+    Arc::new(HostSnapshot::new(hostname, &sample, health))
+}
+
+async fn serve_client(stream: tokio::net::TcpStream, token: &str, latest: watch::Receiver<Arc<HostSnapshot>>) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = tokio::io::BufReader::new(reader);
+
+    let hello = read_request(&mut reader).await?;
+    let reply = match hello {
+        Some(Request::Hello { version, .. }) if version != PROTOCOL_VERSION => Err(format!(
+            "protocol version {} not supported (agent speaks {})", version, PROTOCOL_VERSION
+        )),
+        Some(Request::Hello { token: given, .. }) if token_matches(&given, token) => Ok(()),
+        Some(Request::Hello { .. }) => Err("invalid token".to_string()),
+        Some(_) => Err("expected hello".to_string()),
+        None => return Ok(()),
+    };
+
+    if let Err(message) = reply {
+        send(&mut writer, &Response::Error { message: message.clone() }).await?;
+        anyhow::bail!("rejected: {}", message);
+    }
+    let hostname = latest.borrow().hostname.clone();
+    send(&mut writer, &Response::Welcome { version: PROTOCOL_VERSION, hostname }).await?;
+
+    while let Some(request) = read_request(&mut reader).await? {
+        let response = match request {
+            Request::Snapshot => {
+                let snapshot = (**latest.borrow()).clone();
+                Response::Snapshot { snapshot }
+            }
+            Request::Hello { .. } => Response::Error { message: "already authenticated".to_string() },
+        };
+        send(&mut writer, &response).await?;
+    }
+    Ok(())
+}
+
+/// Next request line, `None` when the client disconnected
+async fn read_request<R: tokio::io::AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Request>> {
+    let mut line = String::new();
+    let read = (&mut *reader).take(MAX_REQUEST_BYTES).read_line(&mut line).await?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') {
+        anyhow::bail!("request longer than {} bytes", MAX_REQUEST_BYTES);
+    }
+    serde_json::from_str(&line).map(Some).context("malformed request")
+}
+
+async fn send<W: tokio::io::AsyncWrite + Unpin>(writer: &mut W, response: &Response) -> Result<()> {
+    let mut line = serde_json::to_vec(response)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    Ok(())
+}
+
+/// A host watched from this machine, through its agent or over ssh
+pub struct RemoteHost {
+    link: Link,
+    last_snapshot: Option<(HostSnapshot, DateTime<Local>)>,  // Last good data and when it arrived
+}
+
+enum Link {
+    Agent(AgentHost),
+    Ssh(SshHost),
+}
+
+impl RemoteHost {
+    pub fn agent(address: &str, token: &str) -> Self {
+        let agent = AgentHost { address: with_default_port(address), token: token.to_string(), connection: None };
+        Self { link: Link::Agent(agent), last_snapshot: None }
+    }
+
+    pub fn ssh(destination: &str) -> Self {
+        Self { link: Link::Ssh(SshHost::new(destination)), last_snapshot: None }
+    }
+
+    /// Where the host is reached, shown until it has reported its name
+    pub fn address(&self) -> &str {
+        match &self.link {
+            Link::Agent(agent) => &agent.address,
+            Link::Ssh(ssh) => &ssh.destination,
+        }
+    }
+
+    pub fn fetch(&mut self) -> Result<HostSnapshot> {
+        let result = match &mut self.link {
+            Link::Agent(agent) => agent.fetch(),
+            Link::Ssh(ssh) => ssh.fetch(),
+        };
+        let snapshot = result.with_context(|| self.address().to_string())?;
+        self.last_snapshot = Some((snapshot.clone(), Local::now()));
+        Ok(snapshot)
+    }
+
+    /// The last snapshot that arrived and when, to keep showing while the
+    /// host is unreachable
+    pub fn last_snapshot(&self) -> Option<&(HostSnapshot, DateTime<Local>)> {
+        self.last_snapshot.as_ref()
+    }
+}
+
+/// Client side of one agent connection. Blocking, driven by the sampler
+/// thread; reconnects on the next fetch after a failure.
+struct AgentHost {
+    address: String,
+    token: String,
+    connection: Option<BufReader<TcpStream>>,
+}
+
+impl AgentHost {
+    fn fetch(&mut self) -> Result<HostSnapshot> {
+        let result = self.try_fetch();
+        if result.is_err() {
+            self.connection = None;
+        }
+        result
+    }
+
+    fn try_fetch(&mut self) -> Result<HostSnapshot> {
+        if self.connection.is_none() {
+            self.connection = Some(self.connect()?);
+        }
+        let connection = self.connection.as_mut().expect("connected above");
+
+        match exchange(connection, &Request::Snapshot)? {
+            Response::Snapshot { snapshot } => Ok(snapshot),
+            Response::Error { message } => anyhow::bail!("agent error: {}", message),
+            Response::Welcome { .. } => anyhow::bail!("unexpected welcome"),
+        }
+    }
+
+    fn connect(&self) -> Result<BufReader<TcpStream>> {
+        let address = self.address.to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("no address found"))?;
+        let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        let mut connection = BufReader::new(stream);
+
+        let hello = Request::Hello { version: PROTOCOL_VERSION, token: self.token.clone() };
+        match exchange(&mut connection, &hello)? {
+            Response::Welcome { .. } => Ok(connection),
+            Response::Error { message } => anyhow::bail!("agent refused connection: {}", message),
+            Response::Snapshot { .. } => anyhow::bail!("unexpected snapshot before welcome"),
+        }
+    }
+}
+
+fn exchange(connection: &mut BufReader<TcpStream>, request: &Request) -> Result<Response> {
+    let mut line = serde_json::to_vec(request)?;
+    line.push(b'\n');
+    connection.get_mut().write_all(&line)?;
+
+    let mut reply = String::new();
+    let read = connection.by_ref().take(MAX_RESPONSE_BYTES).read_line(&mut reply)?;
+    if read == 0 {
+        anyhow::bail!("agent closed the connection");
+    }
+    serde_json::from_str(&reply).context("malformed response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "secret";
+
+    /// Serves a fixed snapshot on a free loopback port, like `run_agent`
+    async fn start_agent() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let snapshot = HostSnapshot {
+            hostname: "node1".to_string(),
+            taken_at: Local::now(),
+            gpus: Vec::new(),
+            processes: ProcessManager::mock_processes(),
+            launchers: Vec::new(),
+            health: HashMap::new(),
+        };
+        // Receivers keep the last value after the sender is gone
+        let (_, latest) = watch::channel(Arc::new(snapshot));
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let latest = latest.clone();
+                tokio::spawn(async move {
+                    let _ = serve_client(stream, TOKEN, latest).await;
+                });
+            }
+        });
+        address
+    }
+
+    /// Sends a hello with the blocking client code and returns the reply
+    async fn hello(address: SocketAddr, version: u32, token: &str) -> Response {
+        let hello = Request::Hello { version, token: token.to_string() };
+        tokio::task::spawn_blocking(move || {
+            let mut connection = BufReader::new(TcpStream::connect(address).unwrap());
+            exchange(&mut connection, &hello).unwrap()
+        }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn welcomes_a_client_with_the_right_token() {
+        let address = start_agent().await;
+        match hello(address, PROTOCOL_VERSION, TOKEN).await {
+            Response::Welcome { version, hostname } => {
+                assert_eq!(version, PROTOCOL_VERSION);
+                assert_eq!(hostname, "node1");
+            }
+            other => panic!("expected welcome, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_a_bad_token() {
+        let address = start_agent().await;
+        match hello(address, PROTOCOL_VERSION, "wrong").await {
+            Response::Error { message } => assert_eq!(message, "invalid token"),
+            other => panic!("expected error, got {:?}", other),
+        }
+
+        let mut host = RemoteHost::agent(&address.to_string(), "wrong");
+        let error = tokio::task::spawn_blocking(move || host.fetch().unwrap_err()).await.unwrap();
+        assert!(format!("{:#}", error).contains("agent refused connection: invalid token"));
+    }
+
+    #[tokio::test]
+    async fn rejects_another_protocol_version() {
+        let address = start_agent().await;
+        match hello(address, PROTOCOL_VERSION + 1, TOKEN).await {
+            Response::Error { message } => {
+                assert!(message.contains(&format!("protocol version {} not supported", PROTOCOL_VERSION + 1)));
+            }
+            other => panic!("expected error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trips_a_snapshot() {
+        let address = start_agent().await;
+        let mut host = RemoteHost::agent(&address.to_string(), TOKEN);
+        let snapshot = tokio::task::spawn_blocking(move || host.fetch().unwrap()).await.unwrap();
+
+        assert_eq!(snapshot.hostname, "node1");
+        let pids: Vec<u32> = snapshot.processes.iter().map(|p| p.pid).collect();
+        let expected: Vec<u32> = ProcessManager::mock_processes().iter().map(|p| p.pid).collect();
+        assert_eq!(pids, expected);
+    }
+}
@@ -0,0 +1,241 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::sync::mpsc::{self, SyncSender};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use crate::gpu::{GpuInfo, GpuManager, SensorError};
+use crate::health::GpuHealthMetrics;
+use crate::process::{GpuProcess, ProcessManager};
+use crate::remote::{HostSnapshot, RemoteHost};
+
+/// Where samples come from
+pub enum Source {
+    Local { gpu_manager: Arc<GpuManager>, process_manager: Box<ProcessManager> },
+    Remote { hosts: Vec<RemoteHost> },
+}
+
+/// One round of collected data. Never modified after it is published.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub taken_at: DateTime<Local>,
+    pub gpus: Vec<GpuInfo>,
+    pub processes: Vec<GpuProcess>,
+    pub launchers: Vec<GpuProcess>,  // Non-GPU parents shown as tree roots
+    pub health: HashMap<String, GpuHealthMetrics>,  // Computed by remote agents, by GPU display id
+    pub hosts: Vec<HostStatus>,      // Remote hosts only
+    pub timings: Vec<CollectorTiming>,
+    pub warnings: Vec<String>,       // Partial failures, e.g. an unreachable agent
+    pub error: Option<String>,       // Set when collection failed; the data is then from the previous snapshot
+}
+
+/// How current a remote host's data is
+#[derive(Debug, Clone)]
+pub struct HostStatus {
+    pub name: String,                        // Reported hostname, or the address until it answered
+    pub last_seen: Option<DateTime<Local>>,  // When its data last arrived
+    pub error: Option<String>,               // Why the latest fetch failed; its data is then stale
+}
+
+impl HostStatus {
+    pub fn is_stale(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Time since the data shown for this host arrived
+    pub fn age(&self) -> Option<chrono::Duration> {
+        self.last_seen.map(|seen| Local::now() - seen)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CollectorTiming {
+    pub name: String,
+    pub elapsed: Duration,
+}
+
+impl Snapshot {
+    pub fn total_time(&self) -> Duration {
+        self.timings.iter().map(|t| t.elapsed).sum()
+    }
+}
+
+/// Collects GPU and process data on its own thread so a slow NVML call, a
+/// scan of thousands of processes or an unresponsive agent never blocks
+/// input handling.
+/// Samples are taken on request and published through a watch channel.
+pub struct Sampler {
+    requests: SyncSender<()>,
+    snapshots: watch::Receiver<Arc<Snapshot>>,
+}
+
+impl Sampler {
+    /// Takes the first sample on the calling thread, so startup fails
+    /// early and the first frame has data, then starts the sampling thread
+    pub fn start(mut source: Source) -> Result<Self> {
+        let first = collect(&mut source)?;
+        let (publisher, snapshots) = watch::channel(Arc::new(first));
+
+        // Room for one queued request; further requests while a sample is
+        // pending are coalesced into it
+        let (requests, pending) = mpsc::sync_channel(1);
+
+        thread::Builder::new()
+            .name("gputop-sampler".to_string())
+            .spawn(move || {
+                // Ends when the app drops its Sampler
+                while pending.recv().is_ok() {
+                    let snapshot = match collect(&mut source) {
+                        Ok(snapshot) => snapshot,
+                        Err(e) => Snapshot {
+                            taken_at: Local::now(),
+                            error: Some(format!("{:#}", e)),
+                            ..(**publisher.borrow()).clone()
+                        },
+                    };
+                    if publisher.send(Arc::new(snapshot)).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self { requests, snapshots })
+    }
+
+    /// Asks for a new sample. Returns immediately; the result shows up in
+    /// `latest` and wakes `subscribe`rs.
+    pub fn request(&self) {
+        // Full means a sample is already queued. If the thread is gone the
+        // last snapshot simply stays on screen.
+        let _ = self.requests.try_send(());
+    }
+
+    pub fn latest(&self) -> Arc<Snapshot> {
+        self.snapshots.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Snapshot>> {
+        self.snapshots.clone()
+    }
+}
+
+fn collect(source: &mut Source) -> Result<Snapshot> {
+    match source {
+        Source::Local { gpu_manager, process_manager } => collect_local(gpu_manager, process_manager),
+        Source::Remote { hosts } => collect_remote(hosts),
+    }
+}
+
+fn collect_local(gpu_manager: &GpuManager, process_manager: &mut ProcessManager) -> Result<Snapshot> {
+    let mut timings = Vec::new();
+    let mut timed = |name: &'static str, started: Instant| {
+        timings.push(CollectorTiming { name: name.to_string(), elapsed: started.elapsed() });
+    };
+
+    // The managers' async methods don't wait on any I/O, so they are
+    // driven directly on this thread
+    let started = Instant::now();
+    let gpus = futures::executor::block_on(gpu_manager.get_gpu_info())?;
+    timed("gpu", started);
+
+    let started = Instant::now();
+    let device_processes = futures::executor::block_on(gpu_manager.get_device_processes())?;
+    timed("gpu procs", started);
+
+    let started = Instant::now();
+    let mut processes = futures::executor::block_on(process_manager.get_gpu_processes(&device_processes))?;
+    if processes.is_empty() && gpus.iter().any(|gpu| gpu.mock) {
+        processes = ProcessManager::mock_processes();
+    }
+    let launchers = process_manager.get_launchers(&processes);
+    timed("processes", started);
+
+    Ok(Snapshot {
+        taken_at: Local::now(),
+        warnings: sensor_failures(&gpus),
+        gpus,
+        processes,
+        launchers,
+        health: HashMap::new(),
+        hosts: Vec::new(),
+        timings,
+        error: None,
+    })
+}
+
+/// Sensors that failed this round; unsupported ones are expected and not
+/// worth a warning
+fn sensor_failures(gpus: &[GpuInfo]) -> Vec<String> {
+    gpus.iter()
+        .flat_map(|gpu| gpu.sensor_errors.iter()
+            .filter(|(_, error)| matches!(error, SensorError::Failed(_)))
+            .map(move |(sensor, error)| format!("GPU {} {}: {}", gpu.display_id(), sensor, error)))
+        .collect()
+}
+
+/// Fetches all remote hosts in parallel and tags their data with the host
+/// name. A host that doesn't answer keeps showing its last data, marked
+/// stale. Fails only when no host has any data.
+fn collect_remote(hosts: &mut [RemoteHost]) -> Result<Snapshot> {
+    let results: Vec<(Result<HostSnapshot>, Duration)> = thread::scope(|scope| {
+        let fetches: Vec<_> = hosts.iter_mut()
+            .map(|host| scope.spawn(move || {
+                let started = Instant::now();
+                (host.fetch(), started.elapsed())
+            }))
+            .collect();
+        fetches.into_iter()
+            .map(|fetch| fetch.join().unwrap_or_else(|_| (Err(anyhow::anyhow!("fetch panicked")), Duration::ZERO)))
+            .collect()
+    });
+
+    let mut snapshot = Snapshot {
+        taken_at: Local::now(),
+        gpus: Vec::new(),
+        processes: Vec::new(),
+        launchers: Vec::new(),
+        health: HashMap::new(),
+        hosts: Vec::new(),
+        timings: Vec::new(),
+        warnings: Vec::new(),
+        error: None,
+    };
+    for (remote, (result, elapsed)) in hosts.iter().zip(results) {
+        let (host, status) = match result {
+            Ok(host) => {
+                snapshot.timings.push(CollectorTiming { name: host.hostname.clone(), elapsed });
+                let status = HostStatus { name: host.hostname.clone(), last_seen: Some(Local::now()), error: None };
+                (host, status)
+            }
+            Err(e) => {
+                let error = format!("{:#}", e);
+                snapshot.warnings.push(error.clone());
+                match remote.last_snapshot() {
+                    Some((host, seen)) => {
+                        let status = HostStatus { name: host.hostname.clone(), last_seen: Some(*seen), error: Some(error) };
+                        (host.clone(), status)
+                    }
+                    None => {
+                        snapshot.hosts.push(HostStatus { name: remote.address().to_string(), last_seen: None, error: Some(error) });
+                        continue;
+                    }
+                }
+            }
+        };
+        snapshot.hosts.push(status);
+        
+        let tag = Some(host.hostname.clone());
+        snapshot.gpus.extend(host.gpus.into_iter().map(|gpu| GpuInfo { host: tag.clone(), ..gpu }));
+        snapshot.processes.extend(host.processes.into_iter().map(|p| GpuProcess { host: tag.clone(), ..p }));
+        snapshot.launchers.extend(host.launchers.into_iter().map(|p| GpuProcess { host: tag.clone(), ..p }));
+        snapshot.health.extend(host.health.into_iter().map(|(id, health)| (format!("{}/{}", host.hostname, id), health)));
+    }
+
+    if snapshot.hosts.iter().all(|host| host.last_seen.is_none()) {
+        anyhow::bail!("no host reachable: {}", snapshot.warnings.join("; "));
+    }
+    snapshot.warnings.extend(sensor_failures(&snapshot.gpus));
+    Ok(snapshot)
+}
@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::fs;
-use std::path::Path;
-use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use anyhow::{Context, Result};
+use sysinfo::{System, SystemExt};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct HealthThresholds {
     pub temperature_warning: f32,
     pub temperature_critical: f32,
@@ -20,7 +24,7 @@ impl Default for HealthThresholds {
         Self {
             temperature_warning: 75.0,
             temperature_critical: 85.0,
-            power_warning: 80.0,  // Percentage of TDP
+            power_warning: 80.0,  // Percentage of enforced power limit
             power_critical: 95.0,
             memory_usage_warning: 85.0,
             memory_usage_critical: 95.0,
@@ -30,7 +34,8 @@ impl Default for HealthThresholds {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NotificationSettings {
     pub enabled: bool,
     pub min_interval_seconds: u64,
@@ -49,10 +54,30 @@ impl Default for NotificationSettings {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnergySettings {
+    pub price_per_kwh: f64,
+    pub currency: String,
+    pub carbon_intensity_g_per_kwh: f64,  // grams CO2e per kWh of the local grid
+}
+
+impl Default for EnergySettings {
+    fn default() -> Self {
+        Self {
+            price_per_kwh: 0.15,
+            currency: "USD".to_string(),
+            carbon_intensity_g_per_kwh: 400.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     pub health_thresholds: HealthThresholds,
     pub notification_settings: NotificationSettings,
+    pub energy_settings: EnergySettings,
     pub update_interval_ms: u64,
     pub max_history_points: usize,
 }
@@ -62,40 +87,382 @@ impl Default for AppSettings {
         Self {
             health_thresholds: HealthThresholds::default(),
             notification_settings: NotificationSettings::default(),
+            energy_settings: EnergySettings::default(),
             update_interval_ms: 1000,
             max_history_points: 300,
         }
     }
 }
 
-pub struct SettingsManager {
-    settings: AppSettings,
-    config_path: std::path::PathBuf,
+pub const MIN_UPDATE_INTERVAL_MS: u64 = 100;
+pub const MAX_UPDATE_INTERVAL_MS: u64 = 60_000;
+pub const MIN_HISTORY_POINTS: usize = 10;
+pub const MAX_HISTORY_POINTS: usize = 86_400;
+
+impl AppSettings {
+    /// Checks relationships the form can't express per field
+    pub fn validate(&self) -> Result<(), String> {
+        let t = &self.health_thresholds;
+        let e = &self.energy_settings;
+        // NaN passes every comparison below
+        let numbers = [
+            t.temperature_warning, t.temperature_critical, t.power_warning, t.power_critical,
+            t.memory_usage_warning, t.memory_usage_critical, t.utilization_low, t.utilization_high,
+        ];
+        if numbers.iter().any(|v| !v.is_finite()) || !e.price_per_kwh.is_finite() || !e.carbon_intensity_g_per_kwh.is_finite() {
+            return Err("Thresholds and energy settings must be finite numbers".to_string());
+        }
+        let pairs = [
+            ("Temperature", t.temperature_warning, t.temperature_critical, "warning", "critical"),
+            ("Power", t.power_warning, t.power_critical, "warning", "critical"),
+            ("Memory", t.memory_usage_warning, t.memory_usage_critical, "warning", "critical"),
+            ("Utilization", t.utilization_low, t.utilization_high, "low", "high"),
+        ];
+        for (name, low, high, low_name, high_name) in pairs {
+            if low >= high {
+                return Err(format!("{} {} ({:.1}) must be below {} ({:.1})", name, low_name, low, high_name, high));
+            }
+        }
+        
+        if !(MIN_UPDATE_INTERVAL_MS..=MAX_UPDATE_INTERVAL_MS).contains(&self.update_interval_ms) {
+            return Err(format!(
+                "Update interval must be between {}ms and {}ms",
+                MIN_UPDATE_INTERVAL_MS, MAX_UPDATE_INTERVAL_MS
+            ));
+        }
+        if !(MIN_HISTORY_POINTS..=MAX_HISTORY_POINTS).contains(&self.max_history_points) {
+            return Err(format!(
+                "Max history points must be between {} and {}",
+                MIN_HISTORY_POINTS, MAX_HISTORY_POINTS
+            ));
+        }
+        if self.energy_settings.price_per_kwh < 0.0 || self.energy_settings.carbon_intensity_g_per_kwh < 0.0 {
+            return Err("Energy price and carbon intensity can't be negative".to_string());
+        }
+        
+        Ok(())
+    }
 }
 
-impl SettingsManager {
-    pub fn new() -> Result<Self> {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?
-            .join("gputop");
+pub const INTERVAL_ENV: &str = "GPUTOP_INTERVAL";
+pub const HISTORY_ENV: &str = "GPUTOP_HISTORY";
+
+/// Resolves a setting by precedence: command line, environment variable,
+/// then the settings file (which itself falls back to the defaults)
+pub fn resolve_override<T>(cli: Option<T>, env_var: &str, from_settings: T) -> Result<T>
+where
+    T: std::str::FromStr,
+{
+    if let Some(value) = cli {
+        return Ok(value);
+    }
+    
+    match std::env::var(env_var) {
+        Ok(value) => value.trim().parse().map_err(|_| {
+            anyhow::anyhow!("Invalid {} value '{}': expected a whole number", env_var, value)
+        }),
+        Err(_) => Ok(from_settings),
+    }
+}
+
+/// Update interval from the command line, environment or settings, in range
+pub fn resolve_update_interval(cli: Option<u64>, settings: &AppSettings) -> Result<u64> {
+    let interval = resolve_override(cli, INTERVAL_ENV, settings.update_interval_ms)?;
+    if !(MIN_UPDATE_INTERVAL_MS..=MAX_UPDATE_INTERVAL_MS).contains(&interval) {
+        anyhow::bail!(
+            "Update interval {}ms is out of range ({}-{}ms)",
+            interval, MIN_UPDATE_INTERVAL_MS, MAX_UPDATE_INTERVAL_MS
+        );
+    }
+    Ok(interval)
+}
+
+/// Editable fields of the settings modal, in display order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingsField {
+    TemperatureWarning,
+    TemperatureCritical,
+    PowerWarning,
+    PowerCritical,
+    MemoryWarning,
+    MemoryCritical,
+    UtilizationLow,
+    UtilizationHigh,
+    NotificationsEnabled,
+    NotificationInterval,
+    ExportNotifications,
+    ProcessNotifications,
+    PricePerKwh,
+    Currency,
+    CarbonIntensity,
+    UpdateInterval,
+    MaxHistoryPoints,
+}
+
+impl SettingsField {
+    pub const ALL: [SettingsField; 17] = [
+        SettingsField::TemperatureWarning,
+        SettingsField::TemperatureCritical,
+        SettingsField::PowerWarning,
+        SettingsField::PowerCritical,
+        SettingsField::MemoryWarning,
+        SettingsField::MemoryCritical,
+        SettingsField::UtilizationLow,
+        SettingsField::UtilizationHigh,
+        SettingsField::NotificationsEnabled,
+        SettingsField::NotificationInterval,
+        SettingsField::ExportNotifications,
+        SettingsField::ProcessNotifications,
+        SettingsField::PricePerKwh,
+        SettingsField::Currency,
+        SettingsField::CarbonIntensity,
+        SettingsField::UpdateInterval,
+        SettingsField::MaxHistoryPoints,
+    ];
+    
+    pub fn section(&self) -> &'static str {
+        match self {
+            SettingsField::TemperatureWarning | SettingsField::TemperatureCritical
+            | SettingsField::PowerWarning | SettingsField::PowerCritical
+            | SettingsField::MemoryWarning | SettingsField::MemoryCritical
+            | SettingsField::UtilizationLow | SettingsField::UtilizationHigh => "📊 HEALTH THRESHOLDS",
+            SettingsField::NotificationsEnabled | SettingsField::NotificationInterval
+            | SettingsField::ExportNotifications | SettingsField::ProcessNotifications => "🔔 NOTIFICATIONS",
+            SettingsField::PricePerKwh | SettingsField::Currency
+            | SettingsField::CarbonIntensity => "💰 ENERGY ACCOUNTING",
+            SettingsField::UpdateInterval | SettingsField::MaxHistoryPoints => "⏱️  PERFORMANCE",
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingsField::TemperatureWarning => "Temperature Warning",
+            SettingsField::TemperatureCritical => "Temperature Critical",
+            SettingsField::PowerWarning => "Power Warning",
+            SettingsField::PowerCritical => "Power Critical",
+            SettingsField::MemoryWarning => "Memory Warning",
+            SettingsField::MemoryCritical => "Memory Critical",
+            SettingsField::UtilizationLow => "Low Utilization",
+            SettingsField::UtilizationHigh => "High Utilization",
+            SettingsField::NotificationsEnabled => "Enabled",
+            SettingsField::NotificationInterval => "Min Interval",
+            SettingsField::ExportNotifications => "Export Notifications",
+            SettingsField::ProcessNotifications => "Process Notifications",
+            SettingsField::PricePerKwh => "Price per kWh",
+            SettingsField::Currency => "Currency",
+            SettingsField::CarbonIntensity => "Carbon Intensity",
+            SettingsField::UpdateInterval => "Update Interval",
+            SettingsField::MaxHistoryPoints => "Max History Points",
+        }
+    }
+    
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SettingsField::TemperatureWarning | SettingsField::TemperatureCritical => "°C",
+            SettingsField::PowerWarning | SettingsField::PowerCritical
+            | SettingsField::MemoryWarning | SettingsField::MemoryCritical
+            | SettingsField::UtilizationLow | SettingsField::UtilizationHigh => "%",
+            SettingsField::NotificationInterval => "s",
+            SettingsField::CarbonIntensity => " gCO2e/kWh",
+            SettingsField::UpdateInterval => "ms",
+            _ => "",
+        }
+    }
+    
+    pub fn is_toggle(&self) -> bool {
+        matches!(self,
+            SettingsField::NotificationsEnabled
+            | SettingsField::ExportNotifications
+            | SettingsField::ProcessNotifications)
+    }
+    
+    /// Current value formatted for display and as the initial edit buffer
+    pub fn value(&self, settings: &AppSettings) -> String {
+        let t = &settings.health_thresholds;
+        let n = &settings.notification_settings;
+        let e = &settings.energy_settings;
+        let yes_no = |b: bool| if b { "Yes" } else { "No" }.to_string();
+        
+        match self {
+            SettingsField::TemperatureWarning => format!("{:.1}", t.temperature_warning),
+            SettingsField::TemperatureCritical => format!("{:.1}", t.temperature_critical),
+            SettingsField::PowerWarning => format!("{:.1}", t.power_warning),
+            SettingsField::PowerCritical => format!("{:.1}", t.power_critical),
+            SettingsField::MemoryWarning => format!("{:.1}", t.memory_usage_warning),
+            SettingsField::MemoryCritical => format!("{:.1}", t.memory_usage_critical),
+            SettingsField::UtilizationLow => format!("{:.1}", t.utilization_low),
+            SettingsField::UtilizationHigh => format!("{:.1}", t.utilization_high),
+            SettingsField::NotificationsEnabled => yes_no(n.enabled),
+            SettingsField::NotificationInterval => n.min_interval_seconds.to_string(),
+            SettingsField::ExportNotifications => yes_no(n.show_export_notifications),
+            SettingsField::ProcessNotifications => yes_no(n.show_process_notifications),
+            SettingsField::PricePerKwh => format!("{:.3}", e.price_per_kwh),
+            SettingsField::Currency => e.currency.clone(),
+            SettingsField::CarbonIntensity => format!("{:.0}", e.carbon_intensity_g_per_kwh),
+            SettingsField::UpdateInterval => settings.update_interval_ms.to_string(),
+            SettingsField::MaxHistoryPoints => settings.max_history_points.to_string(),
+        }
+    }
+    
+    /// Parses `input` into the field, validating the whole settings afterwards.
+    /// `settings` is left untouched on error.
+    pub fn set(&self, settings: &mut AppSettings, input: &str) -> Result<(), String> {
+        let input = input.trim();
+        let mut updated = settings.clone();
+        let t = &mut updated.health_thresholds;
+        let n = &mut updated.notification_settings;
+        let e = &mut updated.energy_settings;
         
-        if !config_dir.exists() {
-            fs::create_dir_all(&config_dir)?;
+        match self {
+            SettingsField::TemperatureWarning => t.temperature_warning = parse_float(input)?,
+            SettingsField::TemperatureCritical => t.temperature_critical = parse_float(input)?,
+            SettingsField::PowerWarning => t.power_warning = parse_percent(input)?,
+            SettingsField::PowerCritical => t.power_critical = parse_percent(input)?,
+            SettingsField::MemoryWarning => t.memory_usage_warning = parse_percent(input)?,
+            SettingsField::MemoryCritical => t.memory_usage_critical = parse_percent(input)?,
+            SettingsField::UtilizationLow => t.utilization_low = parse_percent(input)?,
+            SettingsField::UtilizationHigh => t.utilization_high = parse_percent(input)?,
+            SettingsField::NotificationsEnabled => n.enabled = parse_bool(input)?,
+            SettingsField::NotificationInterval => n.min_interval_seconds = parse_number(input)?,
+            SettingsField::ExportNotifications => n.show_export_notifications = parse_bool(input)?,
+            SettingsField::ProcessNotifications => n.show_process_notifications = parse_bool(input)?,
+            SettingsField::PricePerKwh => e.price_per_kwh = parse_float(input)?,
+            SettingsField::Currency => {
+                if input.is_empty() {
+                    return Err("Currency can't be empty".to_string());
+                }
+                e.currency = input.to_uppercase();
+            }
+            SettingsField::CarbonIntensity => e.carbon_intensity_g_per_kwh = parse_float(input)?,
+            SettingsField::UpdateInterval => updated.update_interval_ms = parse_number(input)?,
+            SettingsField::MaxHistoryPoints => updated.max_history_points = parse_number(input)?,
         }
         
-        let config_path = config_dir.join("settings.json");
-        let settings = if config_path.exists() {
-            Self::load_settings(&config_path)?
-        } else {
-            AppSettings::default()
+        updated.validate()?;
+        *settings = updated;
+        Ok(())
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(input: &str) -> Result<T, String> {
+    input.parse().map_err(|_| format!("'{}' is not a valid number", input))
+}
+
+/// Rejects "NaN" and "inf", which parse as floats
+fn parse_float<T: std::str::FromStr + Into<f64> + Copy>(input: &str) -> Result<T, String> {
+    let value: T = parse_number(input)?;
+    if !value.into().is_finite() {
+        return Err(format!("'{}' is not a valid number", input));
+    }
+    Ok(value)
+}
+
+fn parse_percent(input: &str) -> Result<f32, String> {
+    let value: f32 = parse_float(input.trim_end_matches('%'))?;
+    if !(0.0..=100.0).contains(&value) {
+        return Err("Percentages must be between 0 and 100".to_string());
+    }
+    Ok(value)
+}
+
+fn parse_bool(input: &str) -> Result<bool, String> {
+    match input.to_lowercase().as_str() {
+        "yes" | "y" | "true" | "on" | "1" => Ok(true),
+        "no" | "n" | "false" | "off" | "0" => Ok(false),
+        _ => Err(format!("'{}' is not yes/no", input)),
+    }
+}
+
+const SYSTEM_CONFIG_DIR: &str = "/etc/gputop";
+const PROFILES_KEY: &str = "profiles";
+
+/// Where settings are loaded from. Layers are merged in order: defaults,
+/// the system file in `/etc/gputop`, then the user file (or `--config`).
+/// A profile (`--profile`, or one named after the host) is applied last.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSource {
+    pub path: Option<PathBuf>,
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+}
+
+pub struct SettingsManager {
+    settings: AppSettings,
+    config_path: PathBuf,          // Writable user layer
+    layers: Vec<PathBuf>,          // Files that contributed, lowest priority first
+    profile: Option<String>,
+    requested_profile: Option<String>,
+    watched: Vec<(PathBuf, Option<SystemTime>)>,  // Candidate files and their last seen mtime
+}
+
+impl SettingsManager {
+    pub fn new(source: ConfigSource) -> Result<Self> {
+        let config_path = match source.path {
+            Some(path) => {
+                if !path.exists() {
+                    anyhow::bail!("Config file {} does not exist", path.display());
+                }
+                path
+            }
+            None => {
+                let config_dir = dirs::config_dir()
+                    .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?
+                    .join("gputop");
+                
+                if !config_dir.exists() {
+                    fs::create_dir_all(&config_dir)?;
+                }
+                
+                find_config_file(&config_dir).unwrap_or_else(|| config_dir.join("settings.json"))
+            }
         };
         
+        let watched = watched_files(&config_path);
+        let layers = discover_layers(&config_path);
+        let (settings, profile) = Self::load_layers(&layers, source.profile.as_deref())?;
+        
         Ok(Self {
             settings,
             config_path,
+            layers,
+            profile,
+            requested_profile: source.profile,
+            watched,
         })
     }
     
+    /// Re-reads the layers if any config file was created, changed or
+    /// removed since the last check. Returns `None` when nothing changed;
+    /// on error the current settings are kept.
+    pub fn reload_if_changed(&mut self) -> Option<Result<()>> {
+        let current = watched_files(&self.config_path);
+        if current == self.watched {
+            return None;
+        }
+        self.watched = current;
+        
+        let layers = discover_layers(&self.config_path);
+        Some(Self::load_layers(&layers, self.requested_profile.as_deref()).map(|(settings, profile)| {
+            self.settings = settings;
+            self.profile = profile;
+            self.layers = layers;
+        }))
+    }
+    
     pub fn get_settings(&self) -> &AppSettings {
         &self.settings
     }
@@ -104,20 +471,405 @@ impl SettingsManager {
         &mut self.settings
     }
     
-    pub fn save_settings(&self) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self.settings)?;
-        fs::write(&self.config_path, json)?;
+    /// Writes the settings to the user layer in its format. Only values the
+    /// layer already sets or that differ from the layers below it are
+    /// written, so system defaults aren't frozen into the user file. Other
+    /// keys in the file, such as profiles, are kept; with an active profile
+    /// the settings are stored under that profile instead.
+    pub fn save_settings(&mut self) -> Result<()> {
+        let format = ConfigFormat::of(&self.config_path);
+        let mut document = if self.config_path.exists() {
+            Self::read_layer(&self.config_path)?
+        } else {
+            Value::Object(Map::new())
+        };
+        
+        let settings = serde_json::to_value(&self.settings)?;
+        let base = self.base_value()?;
+        let target = match &self.profile {
+            Some(profile) => document
+                .as_object_mut()
+                .map(|root| root.entry(PROFILES_KEY).or_insert_with(|| Value::Object(Map::new())))
+                .and_then(|profiles| profiles.as_object_mut())
+                .map(|profiles| profiles.entry(profile.clone()).or_insert(Value::Null)),
+            None => Some(&mut document),
+        };
+        
+        match target {
+            Some(target) => update_layer(target, &settings, &base),
+            None => anyhow::bail!("{}: '{}' is not a table", self.config_path.display(), PROFILES_KEY),
+        }
+        
+        let content = match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(&document)?,
+            ConfigFormat::Toml => {
+                strip_nulls(&mut document);
+                toml::to_string(&document)?
+            }
+        };
+        fs::write(&self.config_path, content)
+            .with_context(|| format!("Could not write {}", self.config_path.display()))?;
+        
+        // Our own write isn't an external change to reload
+        self.watched = watched_files(&self.config_path);
+        self.layers = discover_layers(&self.config_path);
         Ok(())
     }
     
-    fn load_settings(path: &Path) -> Result<AppSettings> {
-        let content = fs::read_to_string(path)?;
-        let settings: AppSettings = serde_json::from_str(&content)?;
-        Ok(settings)
+    /// What the layers produce without the part `save_settings` writes: the
+    /// user file's root when saving to it, or every layer's root plus the
+    /// lower layers' entries for the active profile when saving to a profile
+    fn base_value(&self) -> Result<Value> {
+        let mut base = serde_json::to_value(AppSettings::default())?;
+        let mut profile_values = Value::Null;
+        
+        for path in &self.layers {
+            let is_user_layer = *path == self.config_path;
+            if is_user_layer && self.profile.is_none() {
+                continue;
+            }
+            
+            let mut layer = Self::read_layer(path)?;
+            let profiles = layer.as_object_mut().and_then(|l| l.remove(PROFILES_KEY));
+            merge(&mut base, layer);
+            
+            let values = self.profile.as_ref()
+                .and_then(|name| profiles?.as_object_mut()?.remove(name));
+            if let Some(values) = values.filter(|_| !is_user_layer) {
+                merge(&mut profile_values, values);
+            }
+        }
+        
+        if !profile_values.is_null() {
+            merge(&mut base, profile_values);
+        }
+        Ok(base)
+    }
+    
+    fn load_layers(layers: &[PathBuf], profile: Option<&str>) -> Result<(AppSettings, Option<String>)> {
+        let schema = serde_json::to_value(AppSettings::default())?;
+        let mut merged = schema.clone();
+        let mut profiles = Map::new();
+        
+        for path in layers {
+            let mut layer = Self::read_layer(path)?;
+            check_layer(&layer, &schema)
+                .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+            
+            if let Some(Value::Object(layer_profiles)) = layer.as_object_mut().and_then(|l| l.remove(PROFILES_KEY)) {
+                for (name, values) in layer_profiles {
+                    merge(profiles.entry(name).or_insert(Value::Null), values);
+                }
+            }
+            merge(&mut merged, layer);
+        }
+        
+        // An explicit profile must exist; otherwise use one named after the host
+        let profile = match profile {
+            Some(name) if profiles.contains_key(name) => Some(name.to_string()),
+            Some(name) => {
+                let mut available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+                available.sort_unstable();
+                anyhow::bail!(
+                    "Profile '{}' not found. Available profiles: {}",
+                    name,
+                    if available.is_empty() { "none".to_string() } else { available.join(", ") }
+                );
+            }
+            None => System::new().host_name().filter(|host| profiles.contains_key(host)),
+        };
+        
+        if let Some(values) = profile.as_ref().and_then(|name| profiles.remove(name)) {
+            merge(&mut merged, values);
+        }
+        
+        let settings: AppSettings = serde_json::from_value(merged)
+            .context("Invalid settings")?;
+        settings.validate()
+            .map_err(|e| anyhow::anyhow!("Invalid settings{}: {}", profile.as_ref().map(|p| format!(" (profile '{}')", p)).unwrap_or_default(), e))?;
+        
+        Ok((settings, profile))
+    }
+    
+    fn read_layer(path: &Path) -> Result<Value> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        
+        let value: Value = match ConfigFormat::of(path) {
+            ConfigFormat::Json => serde_json::from_str(&content)
+                .with_context(|| format!("{}: invalid JSON", path.display()))?,
+            ConfigFormat::Toml => toml::from_str(&content)
+                .with_context(|| format!("{}: invalid TOML", path.display()))?,
+        };
+        
+        if !value.is_object() {
+            anyhow::bail!("{}: expected a table of settings at the top level", path.display());
+        }
+        Ok(value)
+    }
+    
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+    
+    pub fn layers(&self) -> &[PathBuf] {
+        &self.layers
+    }
+    
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
     }
     
     pub fn reset_to_defaults(&mut self) -> Result<()> {
         self.settings = AppSettings::default();
         self.save_settings()
     }
-}
\ No newline at end of file
+}
+
+fn discover_layers(config_path: &Path) -> Vec<PathBuf> {
+    find_config_file(Path::new(SYSTEM_CONFIG_DIR))
+        .into_iter()
+        .chain(Some(config_path.to_path_buf()).filter(|p| p.exists()))
+        .collect()
+}
+
+fn watched_files(config_path: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let system_dir = Path::new(SYSTEM_CONFIG_DIR);
+    [system_dir.join("settings.toml"), system_dir.join("settings.json"), config_path.to_path_buf()]
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+/// `settings.toml` takes precedence over `settings.json` in the same directory
+fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    ["settings.toml", "settings.json"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Deep-merges `overlay` into `base`; tables merge key by key, anything else replaces
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Writes `settings` into the user layer `target`: keys it already sets are
+/// updated, other values only when they differ from `base`, the settings
+/// the lower layers produce
+fn update_layer(target: &mut Value, settings: &Value, base: &Value) {
+    let Value::Object(settings) = settings else {
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Some(table) = target.as_object_mut() else {
+        return;
+    };
+    
+    for (key, value) in settings {
+        let base_value = base.get(key).unwrap_or(&Value::Null);
+        if value.is_object() {
+            let explicit = table.contains_key(key);
+            let child = table.entry(key.clone()).or_insert_with(|| Value::Object(Map::new()));
+            update_layer(child, value, base_value);
+            if !explicit && child.as_object().is_some_and(|c| c.is_empty()) {
+                table.remove(key);
+            }
+        } else if table.contains_key(key) || !same_value(value, base_value) {
+            table.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// `75` in a file and `75.0` from the settings are the same number
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (a, b) => a == b,
+    }
+}
+
+/// Type-checks a layer against the default settings so errors name the bad
+/// key. Unknown keys are ignored so older versions can read newer files.
+fn check_layer(layer: &Value, schema: &Value) -> Result<(), String> {
+    check_table(layer, schema, "")?;
+    
+    match layer.get(PROFILES_KEY) {
+        None => Ok(()),
+        Some(Value::Object(profiles)) => {
+            for (name, profile) in profiles {
+                let path = format!("{}.{}.", PROFILES_KEY, name);
+                if !profile.is_object() {
+                    return Err(format!("'{}' must be a table", path.trim_end_matches('.')));
+                }
+                check_table(profile, schema, &path)?;
+            }
+            Ok(())
+        }
+        Some(_) => Err(format!("'{}' must be a table", PROFILES_KEY)),
+    }
+}
+
+fn check_table(value: &Value, schema: &Value, prefix: &str) -> Result<(), String> {
+    let (Some(table), Some(schema)) = (value.as_object(), schema.as_object()) else {
+        return Ok(());
+    };
+    
+    for (key, value) in table {
+        let Some(expected) = schema.get(key) else { continue };
+        let path = format!("{}{}", prefix, key);
+        
+        let matches = match expected {
+            Value::Object(_) => {
+                if !value.is_object() {
+                    return Err(format!("'{}' must be a table", path));
+                }
+                check_table(value, expected, &format!("{}.", path))?;
+                true
+            }
+            Value::Bool(_) => value.is_boolean(),
+            Value::String(_) => value.is_string(),
+            Value::Number(n) if n.is_u64() => value.is_u64(),
+            Value::Number(_) => value.is_number(),
+            _ => true,
+        };
+        
+        if !matches {
+            let kind = match expected {
+                Value::Bool(_) => "true or false",
+                Value::String(_) => "a string",
+                Value::Number(n) if n.is_u64() => "a non-negative whole number",
+                _ => "a number",
+            };
+            return Err(format!("'{}' must be {}, found {}", path, kind, value));
+        }
+    }
+    
+    Ok(())
+}
+
+/// TOML has no null: unset optional settings are left out of the file
+fn strip_nulls(value: &mut Value) {
+    if let Value::Object(table) = value {
+        table.retain(|_, v| !v.is_null());
+        table.values_mut().for_each(strip_nulls);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_non_finite_numbers() {
+        let mut settings = AppSettings::default();
+        for input in ["NaN", "nan", "inf", "-inf", "infinity"] {
+            assert!(SettingsField::TemperatureWarning.set(&mut settings, input).is_err(), "{}", input);
+            assert!(SettingsField::PowerWarning.set(&mut settings, input).is_err(), "{}", input);
+            assert!(SettingsField::PricePerKwh.set(&mut settings, input).is_err(), "{}", input);
+        }
+        assert_eq!(settings, AppSettings::default());
+        
+        SettingsField::TemperatureWarning.set(&mut settings, "70").unwrap();
+        assert_eq!(settings.health_thresholds.temperature_warning, 70.0);
+        
+        settings.health_thresholds.temperature_critical = f32::NAN;
+        assert!(settings.validate().is_err());
+    }
+    
+    /// Reads `document` back the way `save_settings` writes and `read_layer` parses it
+    fn toml_round_trip_of(mut document: Value) -> Value {
+        strip_nulls(&mut document);
+        let text = toml::to_string(&document).unwrap();
+        toml::from_str(&text).unwrap()
+    }
+    
+    #[test]
+    fn toml_round_trip() {
+        let settings = serde_json::to_value(AppSettings::default()).unwrap();
+        let mut document = settings.clone();
+        document[PROFILES_KEY] = serde_json::json!({ "gpu-node-1": { "update_interval_ms": 500 }, "node.example.com": {} });
+        assert_eq!(toml_round_trip_of(document.clone()), document);
+        
+        let parsed: AppSettings = serde_json::from_value(toml_round_trip_of(settings)).unwrap();
+        assert_eq!(parsed, AppSettings::default());
+        
+        let document = serde_json::json!({ "quote": "say \"hi\" # not a comment", "path": "C:\\gputop", "control": "tab\t\u{1b}" });
+        assert_eq!(toml_round_trip_of(document.clone()), document);
+    }
+    
+    #[test]
+    fn toml_never_writes_null() {
+        let mut document = serde_json::json!({ "a": null, "t": { "b": null, "c": 1 } });
+        strip_nulls(&mut document);
+        let text = toml::to_string(&document).unwrap();
+        assert!(!text.contains("null"));
+        assert_eq!(toml::from_str::<Value>(&text).unwrap(), serde_json::json!({ "t": { "c": 1 } }));
+    }
+    
+    #[test]
+    fn hand_written_toml_loads() {
+        let dir = std::env::temp_dir().join(format!("gputop-settings-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.toml");
+        fs::write(&path, r#"
+            # Inline tables, literal and multi-line strings are all valid TOML
+            update_interval_ms = 1_000
+            energy_settings = { price_per_kwh = 0.3, currency = 'EUR' }
+            
+            [profiles."node.example.com".health_thresholds]
+            temperature_warning = 70.0
+            
+            [profiles.other]
+            note = """
+            not a setting"""
+        "#).unwrap();
+        
+        let layer = SettingsManager::read_layer(&path).unwrap();
+        assert_eq!(layer["update_interval_ms"], 1000);
+        assert_eq!(layer["energy_settings"]["currency"], "EUR");
+        assert_eq!(layer[PROFILES_KEY]["node.example.com"]["health_thresholds"]["temperature_warning"], 70.0);
+        assert!(SettingsManager::read_layer(&dir.join("missing.toml")).is_err());
+        
+        fs::write(&path, "update_interval_ms = ").unwrap();
+        assert!(SettingsManager::read_layer(&path).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    
+    #[test]
+    fn saving_only_writes_the_user_layer() {
+        let base = serde_json::json!({ "update_interval_ms": 2000, "energy_settings": { "price_per_kwh": 0.3, "currency": "EUR" } });
+        let settings = serde_json::json!({ "update_interval_ms": 2000, "energy_settings": { "price_per_kwh": 0.25, "currency": "EUR" } });
+        
+        // Values inherited from the system layer stay there
+        let mut layer = serde_json::json!({});
+        update_layer(&mut layer, &settings, &base);
+        assert_eq!(layer, serde_json::json!({ "energy_settings": { "price_per_kwh": 0.25 } }));
+        
+        // Keys the user already sets are kept up to date, and other keys kept
+        let mut layer = serde_json::json!({ "update_interval_ms": 500, "profiles": { "a": {} } });
+        update_layer(&mut layer, &settings, &base);
+        assert_eq!(layer, serde_json::json!({
+            "update_interval_ms": 2000,
+            "energy_settings": { "price_per_kwh": 0.25 },
+            "profiles": { "a": {} },
+        }));
+        
+        // Integers in a file equal the float settings they parse to
+        let mut layer = serde_json::json!({});
+        update_layer(&mut layer, &serde_json::json!({ "t": 75.0 }), &serde_json::json!({ "t": 75 }));
+        assert_eq!(layer, serde_json::json!({}));
+    }
+}
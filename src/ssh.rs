@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::collections::HashMap;
+use std::process::{Command, Output, Stdio};
+use crate::nvidia_smi::{self, SmiApp};
+use crate::process::GpuProcess;
+use crate::remote::HostSnapshot;
+
+/// Exit status of a command the remote shell couldn't find
+const COMMAND_NOT_FOUND: i32 = 127;
+
+/// Separates the sections of the nvidia-smi fallback output
+const SECTION_MARKER: &str = "--gputop--";
+
+/// What runs on the remote host each tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RemoteCommand {
+    Unknown,    // Not tried yet: gputop first, nvidia-smi if it is missing
+    Gputop,     // `gputop snapshot --format json`
+    NvidiaSmi,  // nvidia-smi CSV queries; no process details beyond name and memory
+}
+
+/// A host reached with the system `ssh` client, so nothing has to be
+/// installed there. Key-based login is required: ssh runs in batch mode and
+/// never prompts. Connections are multiplexed, so only the first tick pays
+/// for the handshake.
+pub struct SshHost {
+    pub destination: String,  // `[user@]host`, anything `ssh` accepts
+    command: RemoteCommand,
+}
+
+impl SshHost {
+    pub fn new(destination: &str) -> Self {
+        Self { destination: destination.to_string(), command: RemoteCommand::Unknown }
+    }
+
+    pub fn fetch(&mut self) -> Result<HostSnapshot> {
+        if self.command != RemoteCommand::NvidiaSmi {
+            let output = self.run("gputop snapshot --format json")?;
+            match output.status.code() {
+                Some(0) => {
+                    self.command = RemoteCommand::Gputop;
+                    return serde_json::from_slice(&output.stdout).context("malformed gputop snapshot");
+                }
+                Some(COMMAND_NOT_FOUND) if self.command == RemoteCommand::Unknown => {
+                    self.command = RemoteCommand::NvidiaSmi;
+                }
+                _ => return Err(failure(&output)),
+            }
+        }
+
+        let gpu_args = nvidia_smi::query_args("gpu", nvidia_smi::GPU_QUERY).join(" ");
+        let app_args = nvidia_smi::query_args("compute-apps", nvidia_smi::APP_QUERY).join(" ");
+        let script = format!(
+            "hostname && nvidia-smi {} && echo {} && nvidia-smi {}",
+            gpu_args, SECTION_MARKER, app_args
+        );
+        let output = self.run(&script)?;
+        if !output.status.success() {
+            return Err(failure(&output));
+        }
+        parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn run(&self, remote_command: &str) -> Result<Output> {
+        // ssh would parse it as an option such as -oProxyCommand=...
+        if self.destination.starts_with('-') {
+            anyhow::bail!("invalid ssh destination '{}'", self.destination);
+        }
+        let control_path = std::env::temp_dir().join("gputop-ssh-%C");
+        Command::new("ssh")
+            .args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=5"])
+            .args(["-o", "ServerAliveInterval=5", "-o", "ServerAliveCountMax=2"])
+            .args(["-o", "ControlMaster=auto", "-o", "ControlPersist=60"])
+            .arg("-o").arg(format!("ControlPath={}", control_path.display()))
+            .arg("--")
+            .arg(&self.destination)
+            .arg(remote_command)
+            .stdin(Stdio::null())
+            .output()
+            .context("Failed to run ssh")
+    }
+}
+
+/// ssh exits with 255 for its own errors, otherwise with the remote status
+fn failure(output: &Output) -> anyhow::Error {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+    match output.status.code() {
+        Some(255) => anyhow::anyhow!("ssh failed: {}", message),
+        Some(code) => anyhow::anyhow!("remote command exited with {}: {}", code, message),
+        None => anyhow::anyhow!("ssh was killed"),
+    }
+}
+
+/// `hostname`, the GPU CSV, the marker, then the process CSV
+fn parse_nvidia_smi(output: &str) -> Result<HostSnapshot> {
+    let (head, apps) = output.split_once(SECTION_MARKER)
+        .ok_or_else(|| anyhow::anyhow!("incomplete nvidia-smi output"))?;
+    let (hostname, gpus) = head.trim_start().split_once('\n').unwrap_or((head.trim(), ""));
+
+    let gpus = nvidia_smi::parse_gpus(gpus)?;
+    let indices: HashMap<&str, usize> = gpus.iter().map(|gpu| (gpu.uuid.as_str(), gpu.info.index)).collect();
+    let processes = nvidia_smi::parse_apps(apps)?
+        .into_iter()
+        .map(|app| {
+            let gpu_index = indices.get(app.gpu_uuid.as_str()).copied();
+            process_from_app(app, gpu_index)
+        })
+        .collect();
+
+    Ok(HostSnapshot {
+        hostname: hostname.trim().to_string(),
+        taken_at: Local::now(),
+        gpus: gpus.into_iter().map(|gpu| gpu.info).collect(),
+        processes,
+        launchers: Vec::new(),
+        health: HashMap::new(),
+    })
+}
+
+/// nvidia-smi knows a process's name and memory; the rest stays unknown
+fn process_from_app(app: SmiApp, gpu_index: Option<usize>) -> GpuProcess {
+    GpuProcess {
+        pid: app.pid,
+        user: "?".to_string(),
+        command: app.process_name,
+        gpu_usage: None,
+        memory_usage: app.used_memory.unwrap_or(0),
+        encoder_usage: None,
+        decoder_usage: None,
+        priority: 0,
+        io_priority: None,
+        context_id: None,
+        container_id: None,
+        container: None,
+        parent_pid: None,
+        start_time: 0,
+        gpu_index,
+        gpu_instance_id: None,
+        compute_instance_id: None,
+        host: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_destinations_that_look_like_options() {
+        let mut host = SshHost::new("-oProxyCommand=touch /tmp/gputop-pwned");
+        let error = host.fetch().unwrap_err();
+        assert!(error.to_string().contains("invalid ssh destination"));
+    }
+}
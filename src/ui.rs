@@ -1,44 +1,40 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect, Alignment},
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{
-        Block, Borders, BorderType, Clear, Gauge, Paragraph, Row, Table,
-        Tabs, Wrap,
+        Block, Borders, BorderType, Cell, Clear, Gauge, Paragraph, Row, Table,
+        TableState, Tabs, Wrap,
     },
     Frame,
 };
-use crate::app::{App, ViewMode};
+use crate::app::{App, SortColumn, ViewMode};
+use crate::process_tree::{self, ProcessRow};
+use crate::filter::{FilterField, ProcessFilter};
+use crate::process::ProcessSignal;
+use crate::settings::SettingsField;
 use crate::utils;
+use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
 
 pub fn draw(f: &mut Frame, app: &App) {
-    // Check if we need space for status message
-    let status_message_height = if app.status_message.is_some() { 3 } else { 0 };
-    
-    // Optimized layout with no wasted space
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(9),  // Compact header
-            Constraint::Min(8),     // Main content
-            Constraint::Length(3),  // Footer
-            Constraint::Length(status_message_height),  // Status message
-        ])
-        .split(f.size());
+    let layout = screen_layout(f.size(), app);
 
-    draw_header(f, chunks[0], app);
+    draw_header(f, layout.header, app);
     
     match app.view_mode {
-        ViewMode::Processes => draw_processes(f, chunks[1], app),
-        ViewMode::Performance => draw_performance(f, chunks[1], app),
-        ViewMode::Hardware => draw_hardware(f, chunks[1], app),
-        ViewMode::Health => draw_health(f, chunks[1], app),
+        ViewMode::Processes => draw_processes(f, layout.main, app),
+        ViewMode::Performance => draw_performance(f, layout.main, app),
+        ViewMode::Hardware => draw_hardware(f, layout.main, app),
+        ViewMode::Health => draw_health(f, layout.main, app),
+        ViewMode::Cluster => draw_cluster(f, layout.main, app),
     }
     
-    draw_footer(f, chunks[2], app);
+    draw_footer(f, layout.controls, layout.tabs, app);
     
     // Draw status message if present
-    if status_message_height > 0 {
-        draw_status_message(f, chunks[3], app);
+    if app.status_message.is_some() {
+        draw_status_message(f, layout.status, app);
     }
     
     // Draw modals
@@ -58,6 +54,14 @@ pub fn draw(f: &mut Frame, app: &App) {
         draw_process_details_modal(f, app);
     }
     
+    if app.signal_dialog.is_some() {
+        draw_signal_dialog(f, app);
+    }
+    
+    if app.device_control_dialog.is_some() {
+        draw_device_control_dialog(f, app);
+    }
+    
 }
 
 fn draw_header(f: &mut Frame, area: Rect, app: &App) {
@@ -78,20 +82,27 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
 
     // 🔥 GPU Information with modern colors
     let gpu_info = format!(
-        "🔥 {} • 🚗 Driver: {} • 🎯 CUDA: {} • 🧠 Memory: {:.1}GB/{:.1}GB ({:.1}%)", 
+        "🔥 [{}] {} • 🚗 Driver: {} • 🎯 CUDA: {} • 🧠 Memory: {}", 
+        gpu.display_id(),
         gpu.name, 
         gpu.driver_version,
         gpu.cuda_version.as_ref().unwrap_or(&"N/A".to_string()),
-        gpu.memory_used as f64 / (1024.0 * 1024.0 * 1024.0),
-        gpu.memory_total as f64 / (1024.0 * 1024.0 * 1024.0),
-        (gpu.memory_used as f64 / gpu.memory_total as f64) * 100.0
+        memory_text(gpu)
     );
     
+    // A remote host that stopped answering keeps its last data on screen
+    let stale = app.host_status(&gpu.host).filter(|status| status.is_stale());
+    let (title, border_color) = match stale.and_then(|status| status.age()) {
+        _ if gpu.mock => (" ⚠️  MOCK DATA • no GPU backend found, run `gputop doctor` ".to_string(), Color::Red),
+        Some(age) => (format!(" 🖥️  GPU Information • ⏳ Stale for {} ", utils::format_age(age)), Color::Red),
+        None => (" 🖥️  GPU Information ".to_string(), Color::Cyan),
+    };
+    
     let info_block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::Cyan))
-        .title(" 🖥️  GPU Information ")
+        .border_style(Style::default().fg(border_color))
+        .title(title)
         .title_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .style(Style::default().bg(Color::Black));
     
@@ -113,7 +124,7 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
         .split(chunks[1]);
 
     // GPU Usage with color coding
-    let gpu_color = get_usage_color(gpu.utilization);
+    let gpu_color = get_usage_color(gpu.utilization.unwrap_or(0.0));
     let gpu_gauge = Gauge::default()
         .block(Block::default()
             .borders(Borders::ALL)
@@ -122,13 +133,13 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
             .title(" ⚡ GPU ")
             .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)))
         .gauge_style(Style::default().fg(gpu_color).add_modifier(Modifier::BOLD))
-        .ratio(gpu.utilization as f64 / 100.0)
-        .label(format!("{:.1}%", gpu.utilization));
+        .ratio((gpu.utilization.unwrap_or(0.0) as f64 / 100.0).clamp(0.0, 1.0))
+        .label(metric(gpu, "utilization", gpu.utilization, |u| format!("{:.1}%", u)));
     f.render_widget(gpu_gauge, util_chunks[0]);
 
     // Memory Usage
-    let mem_usage = (gpu.memory_used as f64 / gpu.memory_total as f64) * 100.0;
-    let mem_color = get_usage_color(mem_usage as f32);
+    let mem_usage = gpu.memory_percent();
+    let mem_color = get_usage_color(mem_usage.unwrap_or(0.0) as f32);
     let mem_gauge = Gauge::default()
         .block(Block::default()
             .borders(Borders::ALL)
@@ -137,12 +148,12 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
             .title(" 🧠 Memory ")
             .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)))
         .gauge_style(Style::default().fg(mem_color).add_modifier(Modifier::BOLD))
-        .ratio(mem_usage / 100.0)
-        .label(format!("{:.1}%", mem_usage));
+        .ratio((mem_usage.unwrap_or(0.0) / 100.0).clamp(0.0, 1.0))
+        .label(metric(gpu, "memory", mem_usage, |pct| format!("{:.1}%", pct)));
     f.render_widget(mem_gauge, util_chunks[1]);
 
     // Temperature
-    let temp_color = get_temp_color(gpu.temperature);
+    let temp_color = get_temp_color(gpu.temperature.unwrap_or(0.0));
     let temp_gauge = Gauge::default()
         .block(Block::default()
             .borders(Borders::ALL)
@@ -151,8 +162,8 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
             .title(" 🌡️  Temperature ")
             .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)))
         .gauge_style(Style::default().fg(temp_color).add_modifier(Modifier::BOLD))
-        .ratio((gpu.temperature as f64 / 100.0).min(1.0))
-        .label(format!("{:.0}°C", gpu.temperature));
+        .ratio((gpu.temperature.unwrap_or(0.0) as f64 / 100.0).clamp(0.0, 1.0))
+        .label(metric(gpu, "temperature", gpu.temperature, |t| format!("{:.0}°C", t)));
     f.render_widget(temp_gauge, util_chunks[2]);
 
     // 📊 Compact additional metrics with health status
@@ -166,13 +177,13 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
     };
 
     let metrics_text = format!(
-        "⚡ Power: {}W • 🌀 Fan: {}% • 🔧 GPU Clock: {}MHz • 🧠 Mem Clock: {}MHz • {} • 🎯 Processes: {} • 🚦 Status: {}",
-        gpu.power_draw.map_or("N/A".to_string(), |p| format!("{:.0}", p)),
-        gpu.fan_speed.map_or("Auto".to_string(), |f| format!("{:.0}", f)),
-        gpu.gpu_clock.map_or("N/A".to_string(), |c| c.to_string()),
-        gpu.memory_clock.map_or("N/A".to_string(), |c| c.to_string()),
+        "⚡ Power: {} • 🌀 Fan: {} • 🔧 GPU Clock: {} • 🧠 Mem Clock: {} • {} • 🎯 Processes: {} • 🚦 Status: {}",
+        format_power_with_limit(gpu),
+        metric(gpu, "fan_speed", gpu.fan_speed, |f| format!("{:.0}%", f)),
+        metric(gpu, "gpu_clock", gpu.gpu_clock, |c| format!("{}MHz", c)),
+        metric(gpu, "memory_clock", gpu.memory_clock, |c| format!("{}MHz", c)),
         health_info,
-        app.processes.len(),
+        app.visible_processes().len(),
         if gpu.throttled { "🔴 Throttled" } else { "🟢 Normal" }
     );
 
@@ -192,22 +203,216 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(metrics_paragraph, chunks[2]);
 }
 
+/// Columns of the process table and the sort order a header click selects
+type ProcessColumn = (&'static str, Constraint, Option<SortColumn>);
+
+const HOST_COLUMN: ProcessColumn = ("🌐 Host", Constraint::Length(14), Some(SortColumn::Host));
+
+const PROCESS_COLUMNS: [ProcessColumn; 8] = [
+    ("PID", Constraint::Length(8), Some(SortColumn::Pid)),
+    ("👤 User", Constraint::Length(12), Some(SortColumn::User)),
+    ("⚡ GPU%", Constraint::Length(12), Some(SortColumn::GpuUsage)),
+    ("🧠 MEM%", Constraint::Length(8), Some(SortColumn::MemoryUsage)),
+    ("📦 VRAM", Constraint::Length(10), Some(SortColumn::MemoryUsage)),
+    ("🎥 ENC%", Constraint::Length(8), None),
+    ("📺 DEC%", Constraint::Length(8), None),
+    ("🔧 Command", Constraint::Min(25), Some(SortColumn::Command)),
+];
+
+/// Remote views lead with the host each process runs on
+fn process_columns(app: &App) -> Vec<ProcessColumn> {
+    let host = app.remote.then_some(HOST_COLUMN);
+    host.into_iter().chain(PROCESS_COLUMNS.iter().cloned()).collect()
+}
+
+const VIEW_TABS: [(&str, ViewMode); 5] = [
+    ("🔧 Proc", ViewMode::Processes),
+    ("📊 Perf", ViewMode::Performance),
+    ("🖥️ HW", ViewMode::Hardware),
+    ("🏥 Health", ViewMode::Health),
+    ("🌐 Cluster", ViewMode::Cluster),
+];
+
+/// Screen regions, shared by drawing and mouse hit-testing
+pub struct ScreenLayout {
+    pub header: Rect,
+    pub main: Rect,
+    pub controls: Rect,
+    pub tabs: Rect,
+    pub status: Rect,
+}
+
+pub fn screen_layout(area: Rect, app: &App) -> ScreenLayout {
+    // Check if we need space for status message
+    let status_message_height = if app.status_message.is_some() { 3 } else { 0 };
+    
+    // Optimized layout with no wasted space
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(9),  // Compact header
+            Constraint::Min(8),     // Main content
+            Constraint::Length(3),  // Footer
+            Constraint::Length(status_message_height),  // Status message
+        ])
+        .split(area);
+    
+    let footer = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(60),
+            Constraint::Percentage(40),
+        ])
+        .split(chunks[2]);
+    
+    ScreenLayout {
+        header: chunks[0],
+        main: chunks[1],
+        controls: footer[0],
+        tabs: footer[1],
+        status: chunks[3],
+    }
+}
+
+/// A line of the process table: a container group header (holding the
+/// index of the group's first row) or a process row
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TableEntry {
+    Group(usize),
+    Row(usize),
+}
+
+fn table_entries(app: &App, rows: &[ProcessRow]) -> Vec<TableEntry> {
+    let mut entries = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        let new_group = i == 0 || rows[i - 1].process.container_label() != row.process.container_label();
+        if app.group_by_container && new_group {
+            entries.push(TableEntry::Group(i));
+        }
+        entries.push(TableEntry::Row(i));
+    }
+    entries
+}
+
+/// What a click at (`x`, `y`) inside the process table `area` landed on
+pub enum TableHit {
+    Sort(SortColumn),
+    Process(usize),
+}
+
+pub fn process_table_hit(app: &App, area: Rect, x: u16, y: u16) -> Option<TableHit> {
+    // Inside the border: header row, its bottom margin, then the rows
+    let inner = Rect::new(area.x + 1, area.y + 1, area.width.saturating_sub(2), area.height.saturating_sub(2));
+    if !contains(inner, x, y) {
+        return None;
+    }
+    
+    if y == inner.y {
+        let columns = process_columns(app);
+        let mut constraints = Vec::with_capacity(columns.len() * 2);
+        for (_, width, _) in columns.iter() {
+            constraints.push(*width);
+            constraints.push(Constraint::Length(1));  // Column spacing
+        }
+        constraints.pop();
+        let areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constraints)
+            .split(Rect::new(inner.x, y, inner.width, 1));
+        
+        return areas.iter()
+            .step_by(2)
+            .zip(columns.iter())
+            .find(|(column, _)| x >= column.x && x < column.x + column.width)
+            .and_then(|(_, (_, _, sort))| sort.clone())
+            .map(TableHit::Sort);
+    }
+    
+    let line = (y - inner.y).checked_sub(2)? as usize + app.process_table_offset.get();
+    let rows = app.visible_rows();
+    match table_entries(app, &rows).get(line)? {
+        TableEntry::Row(i) => Some(TableHit::Process(*i)),
+        TableEntry::Group(_) => None,
+    }
+}
+
+/// View tab under column `x` of the tabs `area`
+pub fn tab_at(area: Rect, x: u16, y: u16) -> Option<ViewMode> {
+    if !contains(area, x, y) {
+        return None;
+    }
+    
+    // Tabs render as " title " separated by a one-column divider
+    let mut start = area.x + 1;
+    for (title, mode) in VIEW_TABS.iter() {
+        let end = start + 2 + title.width() as u16;
+        if x >= start && x < end {
+            return Some(mode.clone());
+        }
+        start = end + 1;
+    }
+    None
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+    x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height
+}
+
 fn draw_processes(f: &mut Frame, area: Rect, app: &App) {
-    let header_cells = [
-        "PID", "👤 User", "⚡ GPU%", "🧠 MEM%", "📦 VRAM", "🎥 ENC%", "📺 DEC%", "🔧 Command"
-    ]
+    let columns = process_columns(app);
+    let header_cells = columns
     .iter()
-    .map(|h| ratatui::widgets::Cell::from(*h)
-        .style(Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD)));
+    .map(|(title, _, sort)| {
+        let marker = match sort {
+            Some(column) if *column == app.sort_column => if app.sort_ascending { "▲" } else { "▼" },
+            _ => "",
+        };
+        Cell::from(format!("{}{}", title, marker))
+            .style(Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD))
+    });
     
     let header = Row::new(header_cells)
         .height(1)
         .bottom_margin(1)
         .style(Style::default().bg(Color::DarkGray));
     
-    let rows = app.processes.iter().enumerate().map(|(i, process)| {
+    let process_rows = app.visible_rows();
+    let entries = table_entries(app, &process_rows);
+    let mut rows = Vec::with_capacity(entries.len());
+    
+    for entry in &entries {
+        let i = match *entry {
+            // Group header row with the group's totals
+            TableEntry::Group(first) => {
+                let first = process_rows[first].process;
+                let group = first.container_label();
+                let members: Vec<_> = process_rows.iter()
+                    .map(|r| r.process)
+                    .filter(|p| p.container_label() == group)
+                    .collect();
+                let icon = first.container.as_ref().map_or("🖥️", |c| c.runtime.emoji());
+                let host = app.remote.then(String::new);
+                rows.push(Row::new(host.into_iter().chain([
+                    String::new(),
+                    format!("{} procs", members.len()),
+                    members.iter().map(|p| p.gpu_usage).fold(None, process_tree::sum_usage)
+                        .map_or("Σ N/A".to_string(), |usage| format!("Σ {:.1}%", usage)),
+                    String::new(),
+                    format!("{}MB", members.iter().map(|p| p.memory_usage).sum::<u64>() / (1024 * 1024)),
+                    String::new(),
+                    String::new(),
+                    format!("{} {}", icon, group.as_deref().unwrap_or("host")),
+                ]))
+                .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+                continue;
+            }
+            TableEntry::Row(i) => i,
+        };
+        let row = &process_rows[i];
+        let process = row.process;
+        
         let style = if Some(i) == app.selected_process {
             Style::default()
                 .bg(Color::Blue)
@@ -221,55 +426,120 @@ fn draw_processes(f: &mut Frame, area: Rect, app: &App) {
             Style::default().fg(Color::White)
         };
         
-        let memory_mb = process.memory_usage / (1024 * 1024);
-        let memory_pct = if let Some(gpu) = app.gpus.get(app.current_gpu) {
-            (process.memory_usage as f64 / gpu.memory_total as f64) * 100.0
-        } else {
-            0.0
+        // Parents in the tree view show their subtree's totals
+        let rolled_up = row.has_children;
+        let gpu_usage = row.total_gpu_usage;
+        let memory_mb = row.total_memory / (1024 * 1024);
+        let memory_pct = app.gpus.get(app.current_gpu)
+            .and_then(|gpu| gpu.memory_total)
+            .filter(|total| *total > 0)
+            .map_or(0.0, |total| (row.total_memory as f64 / total as f64) * 100.0);
+        
+        let tree_marker = match (row.has_children, row.collapsed) {
+            (true, true) => "▸ ",
+            (true, false) => "▾ ",
+            (false, _) if app.tree_view => "  ",
+            _ => "",
         };
         
-        let container_indicator = if process.container_id.is_some() { "🐳 " } else { "" };
+        let container_indicator = match &process.container {
+            Some(container) if !app.group_by_container => format!("{} ", container.runtime.emoji()),
+            _ => String::new(),
+        };
         
         // Color code GPU usage
-        let gpu_usage_color = if process.gpu_usage > 80.0 { "🔴" }
-        else if process.gpu_usage > 50.0 { "🟡" }
-        else if process.gpu_usage > 0.0 { "🟢" }
-        else { "⚫" };
+        let gpu_usage_color = match gpu_usage {
+            Some(usage) if usage > 80.0 => "🔴",
+            Some(usage) if usage > 50.0 => "🟡",
+            Some(usage) if usage > 0.0 => "🟢",
+            _ => "⚫",
+        };
+        let total_marker = if rolled_up { "Σ" } else { "" };
         
-        Row::new([
-            format!("{}", process.pid),
-            process.user.clone(),
-            format!("{} {:.1}%", gpu_usage_color, process.gpu_usage),
-            format!("{:.1}%", memory_pct),
-            format!("{}MB", memory_mb),
-            format!("{:.1}%", process.encoder_usage),
-            format!("{:.1}%", process.decoder_usage),
-            format!("{}{}", container_indicator, process.command),
-        ])
-        .style(style)
-    });
+        let filter = app.process_filter.as_ref();
+        let host = app.remote.then(|| {
+            let name = process.host.clone().unwrap_or_default();
+            match app.host_status(&process.host) {
+                Some(status) if status.is_stale() => Cell::from(format!("⏳ {}", name)).style(Style::default().fg(Color::DarkGray)),
+                _ => Cell::from(name),
+            }
+        });
+        rows.push(Row::new(host.into_iter().chain([
+            Cell::from(format!("{}", process.pid)),
+            Cell::from(highlight_matches(filter, FilterField::User, "", &process.user)),
+            Cell::from(format!(
+                "{} {}{}",
+                gpu_usage_color,
+                total_marker,
+                gpu_usage.map_or("N/A".to_string(), |u| format!("{:.1}%", u))
+            )),
+            Cell::from(format!("{:.1}%", memory_pct)),
+            Cell::from(format!("{}{}MB", total_marker, memory_mb)),
+            Cell::from(process.encoder_usage.map_or("N/A".to_string(), |u| format!("{:.1}%", u))),
+            Cell::from(process.decoder_usage.map_or("N/A".to_string(), |u| format!("{:.1}%", u))),
+            Cell::from(highlight_matches(
+                filter,
+                FilterField::Command,
+                &format!("{}{}{}", row.prefix, tree_marker, container_indicator),
+                &process.command,
+            )),
+        ]))
+        .style(style));
+    }
 
+    let widths: Vec<Constraint> = columns.iter().map(|(_, width, _)| *width).collect();
     let table = Table::new(rows)
         .header(header)
         .block(Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
             .border_style(Style::default().fg(Color::Green))
-            .title(" 🔧 GPU Processes ")
+            .title(format!(
+                " 🔧 GPU Processes{}{}{} ",
+                match app.gpus.get(app.current_gpu) {
+                    Some(gpu) if gpu.mig.is_some() => format!(" • MIG {}", gpu.display_id()),
+                    _ => String::new(),
+                },
+                if app.tree_view { " • 🌳 Tree (←/→ collapse/expand)" } else { "" },
+                if app.process_filter.is_some() {
+                    format!(" • 🔍 {}/{} shown", app.visible_processes().len(), app.processes.len())
+                } else {
+                    String::new()
+                }
+            ))
             .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
             .style(Style::default().bg(Color::Black)))
-        .widths(&[
-            Constraint::Length(8),   // PID
-            Constraint::Length(12),  // User
-            Constraint::Length(12),  // GPU %
-            Constraint::Length(8),   // MEM %
-            Constraint::Length(10),  // VRAM
-            Constraint::Length(8),   // ENC %
-            Constraint::Length(8),   // DEC %
-            Constraint::Min(25),     // Command
-        ]);
-
-    f.render_widget(table, area);
+        .widths(&widths);
+
+    // Ratatui scrolls to keep the selection visible; the offset is kept
+    // across frames and for mapping mouse clicks to rows
+    let selected = app.selected_process
+        .and_then(|selected| entries.iter().position(|e| *e == TableEntry::Row(selected)));
+    let mut state = TableState::default()
+        .with_offset(app.process_table_offset.get().min(entries.len().saturating_sub(1)))
+        .with_selected(selected);
+    f.render_stateful_widget(table, area, &mut state);
+    app.process_table_offset.set(state.offset());
+}
+
+/// Cell text with the parts matched by the search filter highlighted
+fn highlight_matches<'a>(filter: Option<&ProcessFilter>, field: FilterField, prefix: &str, text: &'a str) -> Line<'a> {
+    let mut spans = vec![Span::raw(prefix.to_string())];
+    let mut last = 0;
+    
+    if let Some(filter) = filter {
+        for range in filter.highlights(field, text) {
+            spans.push(Span::raw(&text[last..range.start]));
+            spans.push(Span::styled(
+                &text[range.start..range.end],
+                Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ));
+            last = range.end;
+        }
+    }
+    
+    spans.push(Span::raw(&text[last..]));
+    Line::from(spans)
 }
 
 fn draw_performance(f: &mut Frame, area: Rect, app: &App) {
@@ -304,8 +574,9 @@ fn draw_performance(f: &mut Frame, area: Rect, app: &App) {
     let bottom_row = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(50),  // Temperature
-            Constraint::Percentage(50),  // Power & Clock
+            Constraint::Percentage(34),  // Temperature
+            Constraint::Percentage(33),  // Power & Clock
+            Constraint::Percentage(33),  // Media engines
         ])
         .split(charts_chunks[1]);
 
@@ -314,6 +585,7 @@ fn draw_performance(f: &mut Frame, area: Rect, app: &App) {
     draw_memory_usage_chart(f, top_row[1], app);
     draw_temperature_chart(f, bottom_row[0], app);
     draw_power_clock_chart(f, bottom_row[1], app);
+    draw_media_panel(f, bottom_row[2], app);
 }
 
 fn draw_realtime_metrics(f: &mut Frame, area: Rect, app: &App) {
@@ -323,19 +595,16 @@ fn draw_realtime_metrics(f: &mut Frame, area: Rect, app: &App) {
     };
 
     let current_time = chrono::Local::now().format("%H:%M:%S");
-    let memory_pct = (gpu.memory_used as f64 / gpu.memory_total as f64) * 100.0;
     
     let metrics_text = format!(
-        "🕐 {} • ⚡ GPU: {:.1}% • 🧠 Memory: {:.1}% ({:.1}GB/{:.1}GB) • 🌡️ Temp: {:.0}°C • ⚡ Power: {}W • 🔧 GPU: {}MHz • 🧠 Mem: {}MHz",
+        "🕐 {} • ⚡ GPU: {} • 🧠 Memory: {} • 🌡️ Temp: {} • ⚡ Power: {} • 🔧 GPU: {} • 🧠 Mem: {}",
         current_time,
-        gpu.utilization,
-        memory_pct,
-        gpu.memory_used as f64 / (1024.0 * 1024.0 * 1024.0),
-        gpu.memory_total as f64 / (1024.0 * 1024.0 * 1024.0),
-        gpu.temperature,
-        gpu.power_draw.map_or("N/A".to_string(), |p| format!("{:.0}", p)),
-        gpu.gpu_clock.map_or("N/A".to_string(), |c| c.to_string()),
-        gpu.memory_clock.map_or("N/A".to_string(), |c| c.to_string())
+        metric(gpu, "utilization", gpu.utilization, |u| format!("{:.1}%", u)),
+        memory_text(gpu),
+        metric(gpu, "temperature", gpu.temperature, |t| format!("{:.0}°C", t)),
+        metric(gpu, "power_draw", gpu.power_draw, |p| format!("{:.0}W", p)),
+        metric(gpu, "gpu_clock", gpu.gpu_clock, |c| format!("{}MHz", c)),
+        metric(gpu, "memory_clock", gpu.memory_clock, |c| format!("{}MHz", c))
     );
 
     let metrics_block = Block::default()
@@ -357,7 +626,7 @@ fn draw_realtime_metrics(f: &mut Frame, area: Rect, app: &App) {
 fn draw_gpu_utilization_chart(f: &mut Frame, area: Rect, app: &App) {
     let data: Vec<(f64, f64)> = app.history.iter()
         .enumerate()
-        .map(|(i, h)| (i as f64, h.utilization as f64))
+        .filter_map(|(i, h)| h.utilization.map(|value| (i as f64, value as f64)))
         .collect();
 
     let chart_text = create_time_series_chart(&data, "GPU Utilization %", Color::Green, 0.0, 100.0);
@@ -378,7 +647,7 @@ fn draw_gpu_utilization_chart(f: &mut Frame, area: Rect, app: &App) {
 fn draw_memory_usage_chart(f: &mut Frame, area: Rect, app: &App) {
     let data: Vec<(f64, f64)> = app.history.iter()
         .enumerate()
-        .map(|(i, h)| (i as f64, h.memory_usage as f64))
+        .filter_map(|(i, h)| h.memory_usage.map(|value| (i as f64, value as f64)))
         .collect();
 
     let chart_text = create_time_series_chart(&data, "Memory Usage %", Color::Blue, 0.0, 100.0);
@@ -399,7 +668,7 @@ fn draw_memory_usage_chart(f: &mut Frame, area: Rect, app: &App) {
 fn draw_temperature_chart(f: &mut Frame, area: Rect, app: &App) {
     let data: Vec<(f64, f64)> = app.history.iter()
         .enumerate()
-        .map(|(i, h)| (i as f64, h.temperature as f64))
+        .filter_map(|(i, h)| h.temperature.map(|value| (i as f64, value as f64)))
         .collect();
 
     let chart_text = create_time_series_chart(&data, "Temperature °C", Color::Red, 20.0, 100.0);
@@ -425,17 +694,17 @@ fn draw_power_clock_chart(f: &mut Frame, area: Rect, app: &App) {
 
     let power_text = format!(
         "⚡ POWER & CLOCKS\n\n\
-        🔥 Power Draw: {}W\n\
-        🔧 GPU Clock: {}MHz\n\
-        🧠 Memory Clock: {}MHz\n\
-        🌀 Fan Speed: {}%\n\
+        🔥 Power Draw: {}\n\
+        🔧 GPU Clock: {}\n\
+        🧠 Memory Clock: {}\n\
+        🌀 Fan Speed: {}\n\
         📊 Processes: {}\n\
         🚦 Status: {}",
-        gpu.power_draw.map_or("N/A".to_string(), |p| format!("{:.0}", p)),
-        gpu.gpu_clock.map_or("N/A".to_string(), |c| c.to_string()),
-        gpu.memory_clock.map_or("N/A".to_string(), |c| c.to_string()),
-        gpu.fan_speed.map_or("Auto".to_string(), |f| format!("{:.0}", f)),
-        app.processes.len(),
+        format_power_with_limit(gpu),
+        metric(gpu, "gpu_clock", gpu.gpu_clock, |c| format!("{}MHz", c)),
+        metric(gpu, "memory_clock", gpu.memory_clock, |c| format!("{}MHz", c)),
+        metric(gpu, "fan_speed", gpu.fan_speed, |f| format!("{:.0}%", f)),
+        app.visible_processes().len(),
         if gpu.throttled { "🔴 Throttled" } else { "🟢 Normal" }
     );
     
@@ -453,6 +722,60 @@ fn draw_power_clock_chart(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(power_paragraph, area);
 }
 
+/// NVENC/NVDEC load and the encoder sessions behind it
+fn draw_media_panel(f: &mut Frame, area: Rect, app: &App) {
+    let gpu = match app.gpus.get(app.current_gpu) {
+        Some(gpu) => gpu,
+        None => return,
+    };
+
+    let mut lines = vec![
+        format!("🎥 NVENC: {}", metric(gpu, "encoder_utilization", gpu.encoder_utilization, |u| format!("{:.0}%", u))),
+        format!("📺 NVDEC: {}", metric(gpu, "decoder_utilization", gpu.decoder_utilization, |u| format!("{:.0}%", u))),
+    ];
+    match &gpu.encoder {
+        Some(encoder) => {
+            lines.push(format!(
+                "🎞️  Sessions: {} • {} fps • {:.1} ms",
+                encoder.session_count,
+                encoder.average_fps,
+                encoder.average_latency_us as f64 / 1000.0
+            ));
+            lines.push(String::new());
+            for session in &encoder.sessions {
+                let command = app.processes.iter()
+                    .find(|p| p.pid == session.pid && p.host == gpu.host)
+                    .map_or("?", |p| p.command.as_str());
+                lines.push(format!(
+                    "▶ {} {}x{} {}fps {:.1}ms • {} ({})",
+                    session.codec, session.width, session.height, session.fps,
+                    session.latency_us as f64 / 1000.0, command, session.pid
+                ));
+            }
+            if encoder.sessions.len() < encoder.session_count as usize {
+                lines.push(format!(
+                    "{} session(s) without details",
+                    encoder.session_count as usize - encoder.sessions.len()
+                ));
+            }
+        }
+        None => lines.push(format!("🎞️  Sessions: N/A ({})", gpu.missing_reason("encoder"))),
+    }
+
+    let media_paragraph = Paragraph::new(lines.join("\n"))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Magenta))
+            .title(" 🎬 Media ")
+            .title_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .style(Style::default().bg(Color::Black)))
+        .style(Style::default().fg(Color::White))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+    f.render_widget(media_paragraph, area);
+}
+
 fn create_time_series_chart(data: &[(f64, f64)], _label: &str, color: Color, min_val: f64, max_val: f64) -> String {
     if data.is_empty() {
         return "No data available".to_string();
@@ -516,30 +839,57 @@ fn draw_hardware(f: &mut Frame, area: Rect, app: &App) {
             🏭 Vendor: {:?}\n\
             🚗 Driver Version: {}\n\
             🎯 CUDA Version: {}\n\
-            🧠 Memory Total: {:.2} GB\n\
-            📊 Memory Used: {:.2} GB\n\
-            💿 Memory Free: {:.2} GB\n\
-            🌡️  Temperature: {:.0}°C\n\
-            🌀 Fan Speed: {}%\n\
-            ⚡ Power Draw: {}W\n\
-            🔥 GPU Clock: {}MHz\n\
-            🧠 Memory Clock: {}MHz\n\
-            🚦 Throttled: {}",
+            🧠 Memory Total: {}\n\
+            📊 Memory Used: {}\n\
+            💿 Memory Free: {}\n\
+            🌡️  Temperature: {}\n\
+            🌀 Fans: {}\n\
+            ⚡ Power Draw: {}\n\
+            🔌 Power Limit: {}\n\
+            🔥 GPU Clock: {}\n\
+            🧠 Memory Clock: {}\n\
+            ⏲️  Application Clocks: {}\n\
+            🔒 Compute Mode: {}\n\
+            🚦 Throttled: {}\n\n\
+            m = Cycle compute mode • >/< = Raise/lower app clocks • x = Reset clocks",
             gpu.name,
             gpu.vendor,
             gpu.driver_version,
             gpu.cuda_version.as_ref().unwrap_or(&"N/A".to_string()),
-            gpu.memory_total as f64 / (1024.0 * 1024.0 * 1024.0),
-            gpu.memory_used as f64 / (1024.0 * 1024.0 * 1024.0),
-            (gpu.memory_total - gpu.memory_used) as f64 / (1024.0 * 1024.0 * 1024.0),
-            gpu.temperature,
-            gpu.fan_speed.map_or("Auto".to_string(), |f| format!("{:.0}", f)),
-            gpu.power_draw.map_or("N/A".to_string(), |p| format!("{:.0}", p)),
-            gpu.gpu_clock.map_or("N/A".to_string(), |c| c.to_string()),
-            gpu.memory_clock.map_or("N/A".to_string(), |c| c.to_string()),
+            metric(gpu, "memory", gpu.memory_total, |total| format!("{:.2} GB", total as f64 / GB)),
+            metric(gpu, "memory", gpu.memory_used, |used| format!("{:.2} GB", used as f64 / GB)),
+            metric(gpu, "memory", gpu.memory_total.zip(gpu.memory_used), |(total, used)| {
+                format!("{:.2} GB", total.saturating_sub(used) as f64 / GB)
+            }),
+            metric(gpu, "temperature", gpu.temperature, |t| format!("{:.0}°C", t)),
+            fans_text(gpu),
+            metric(gpu, "power_draw", gpu.power_draw, |p| format!("{:.0}W", p)),
+            metric(gpu, "power_limits", gpu.power_limits.as_ref(), |l| format!(
+                "{:.0}W (default {}, range {})",
+                l.enforced,
+                metric(gpu, "power_limit_default", l.default, |d| format!("{:.0}W", d)),
+                metric(gpu, "power_limit_range", l.min.zip(l.max), |(min, max)| format!("{:.0}-{:.0}W", min, max)),
+            )),
+            metric(gpu, "gpu_clock", gpu.gpu_clock, |c| format!("{}MHz", c)),
+            metric(gpu, "memory_clock", gpu.memory_clock, |c| format!("{}MHz", c)),
+            metric(
+                gpu,
+                if gpu.app_gpu_clock.is_none() { "app_gpu_clock" } else { "app_memory_clock" },
+                gpu.app_gpu_clock.zip(gpu.app_memory_clock),
+                |(graphics, memory)| format!("{}/{} MHz", graphics, memory),
+            ),
+            metric(gpu, "compute_mode", gpu.compute_mode, |m| m.text().to_string()),
             if gpu.throttled { "🔴 Yes" } else { "🟢 No" }
         );
 
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(60),  // Current device details
+                Constraint::Percentage(40),  // Device tree
+            ])
+            .split(area);
+
         let hardware_paragraph = Paragraph::new(info_text)
             .block(Block::default()
                 .borders(Borders::ALL)
@@ -550,8 +900,54 @@ fn draw_hardware(f: &mut Frame, area: Rect, app: &App) {
                 .style(Style::default().bg(Color::Black)))
             .style(Style::default().fg(Color::White))
             .wrap(Wrap { trim: true });
-        f.render_widget(hardware_paragraph, area);
+        f.render_widget(hardware_paragraph, chunks[0]);
+
+        draw_device_tree(f, chunks[1], app);
+    }
+}
+
+fn draw_device_tree(f: &mut Frame, area: Rect, app: &App) {
+    let mut lines = Vec::new();
+    for (i, gpu) in app.gpus.iter().enumerate() {
+        let marker = if i == app.current_gpu { "▶" } else { " " };
+        let processes = app.processes.iter().filter(|p| gpu.runs_process(p)).count();
+        let memory = memory_text(gpu);
+        
+        match &gpu.mig {
+            None => {
+                let mig_count = app.gpus.iter().filter(|g| g.index == gpu.index && g.mig.is_some()).count();
+                lines.push(format!("{} 🖥️  GPU {} • {}", marker, gpu.index, gpu.name));
+                lines.push(format!(
+                    "    ⚡ {} • 🧠 {} • 🎯 {} procs{}",
+                    metric(gpu, "utilization", gpu.utilization, |u| format!("{:.0}%", u)),
+                    memory,
+                    processes,
+                    if mig_count > 0 { format!(" • 🧩 MIG x{}", mig_count) } else { String::new() }
+                ));
+            }
+            Some(mig) => {
+                lines.push(format!(
+                    "{}   └─ 🧩 {} • GI {} / CI {}",
+                    marker, gpu.display_id(), mig.gpu_instance_id, mig.compute_instance_id
+                ));
+                lines.push(format!("       🧠 {} • 🎯 {} procs", memory, processes));
+            }
+        }
     }
+    lines.push(String::new());
+    lines.push("[ / ] cycle devices • F1-F4 select GPU".to_string());
+
+    let tree_paragraph = Paragraph::new(lines.join("\n"))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" 🧩 Devices ")
+            .title_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .style(Style::default().bg(Color::Black)))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false });
+    f.render_widget(tree_paragraph, area);
 }
 
 fn draw_health(f: &mut Frame, area: Rect, app: &App) {
@@ -628,23 +1024,23 @@ fn draw_health_overview(f: &mut Frame, area: Rect, health: &crate::health::GpuHe
     f.render_widget(health_paragraph, chunks[0]);
 
     // Temperature Health
-    let temp_status = if health.temperature.current >= health.temperature.critical {
-        ("🔥", "Critical", Color::Red)
-    } else if health.temperature.current >= health.temperature.max_safe {
-        ("🌡️", "High", Color::Yellow)
-    } else if health.temperature.current > 70.0 {
-        ("🟡", "Warm", Color::Yellow)
-    } else {
-        ("❄️", "Cool", Color::Green)
+    let temp_status = match health.temperature.current {
+        None => ("⚪", "No sensor", Color::DarkGray),
+        Some(current) if current >= health.temperature.critical => ("🔥", "Critical", Color::Red),
+        Some(current) if current >= health.temperature.max_safe => ("🌡️", "High", Color::Yellow),
+        Some(current) if current > 70.0 => ("🟡", "Warm", Color::Yellow),
+        Some(_) => ("❄️", "Cool", Color::Green),
     };
 
+    let celsius = |t: Option<f32>| t.map_or("N/A".to_string(), |t| format!("{:.0}°C", t));
     let temp_text = format!(
-        "🌡️ Temperature\n\n{} {}\n{:.0}°C\n\nTrend: {:+.1}°C/5min\nPeak: {:.0}°C",
+        "🌡️ Temperature\n\n{} {}\n{}\n\nTrend: {:+.1}°C/5min\nPeak: {}\nFans: {}",
         temp_status.0,
         temp_status.1,
-        health.temperature.current,
+        celsius(health.temperature.current),
         health.temperature.trend_5min,
-        health.temperature.peak_today
+        celsius(health.temperature.peak_today),
+        health.fans.speed.map_or("N/A".to_string(), |s| format!("{:.0}% ({:+.0}/5min)", s, health.fans.trend_5min))
     );
 
     let temp_paragraph = Paragraph::new(temp_text)
@@ -661,11 +1057,14 @@ fn draw_health_overview(f: &mut Frame, area: Rect, health: &crate::health::GpuHe
 
     // Power Health
     let power_text = format!(
-        "⚡ Power\n\nCurrent: {:.0}W\nEfficiency: {:.1}\nAvg 1hr: {:.0}W\n\nSpikes: {}",
+        "⚡ Power\n\nCurrent: {:.0}W\nLimit: {}\nAvg 1hr: {:.0}W\n\nSession: {:.2}Wh",
         health.power.current_draw,
-        health.power.efficiency,
+        match (health.power.power_limit, health.power.limit_usage) {
+            (Some(limit), Some(usage)) => format!("{:.0}W ({:.0}%)", limit, usage),
+            _ => "N/A".to_string(),
+        },
         health.power.avg_draw_1hr,
-        health.power.power_spikes
+        health.power.session_energy_wh
     );
 
     let power_paragraph = Paragraph::new(power_text)
@@ -729,7 +1128,7 @@ fn draw_health_details(f: &mut Frame, area: Rect, health: &crate::health::GpuHea
         .split(area);
 
     // Recent Alerts
-    let recent_alerts = app.health_monitor.get_recent_alerts(10);
+    let recent_alerts = app.recent_alerts(10);
     let alerts_text = if recent_alerts.is_empty() {
         "🟢 No recent alerts\n\nAll systems operating normally.\nHealth monitoring is active.".to_string()
     } else {
@@ -764,14 +1163,16 @@ fn draw_health_details(f: &mut Frame, area: Rect, health: &crate::health::GpuHea
     let details_text = format!(
         "📊 DETAILED METRICS\n\n\
         🌡️ TEMPERATURE:\n\
-        • Current: {:.1}°C\n\
+        • Current: {}\n\
         • Safe Limit: {:.1}°C\n\
         • Critical: {:.1}°C\n\
         • Time >80°C: {}min\n\n\
         ⚡ POWER & PERFORMANCE:\n\
         • Current Draw: {:.0}W\n\
+        • Of Power Limit: {}\n\
         • Efficiency: {:.2} util/W\n\
-        • Power Spikes: {}\n\n\
+        • Power Spikes: {}\n\
+        • Session Energy: {:.2} Wh\n\n\
         🧠 MEMORY HEALTH:\n\
         • Usage Trend: {:+.0} MB/min\n\
         • Fragmentation: {:.1}%\n\
@@ -779,13 +1180,15 @@ fn draw_health_details(f: &mut Frame, area: Rect, health: &crate::health::GpuHea
         🚦 SYSTEM STATUS:\n\
         • Throttling: {}\n\
         • Monitoring: {:.1}h",
-        health.temperature.current,
+        health.temperature.current.map_or("N/A".to_string(), |t| format!("{:.1}°C", t)),
         health.temperature.max_safe,
         health.temperature.critical,
         health.temperature.time_above_80c / 60,
         health.power.current_draw,
+        health.power.limit_usage.map_or("N/A".to_string(), |u| format!("{:.0}%", u)),
         health.power.efficiency,
         health.power.power_spikes,
+        health.power.session_energy_wh,
         health.memory.usage_trend,
         health.memory.fragmentation_score * 100.0,
         health.memory.leak_suspicion * 100.0,
@@ -806,36 +1209,200 @@ fn draw_health_details(f: &mut Frame, area: Rect, health: &crate::health::GpuHea
     f.render_widget(details_paragraph, chunks[1]);
 }
 
-fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
-    let help_text = match app.view_mode {
-        ViewMode::Processes => "q=Quit • ↑↓=Nav • Enter=Details • Del=Kill • Ctrl+E=Export • h=Help",
-        ViewMode::Performance => "q=Quit • F1-F4=GPU • Ctrl+E=Export • h=Help",
-        ViewMode::Hardware => "q=Quit • F1-F4=GPU • Ctrl+E=Export • h=Help",
-        ViewMode::Health => "q=Quit • F1-F4=GPU • Ctrl+E=Export • h=Help",
-    };
+/// Width of a heatmap cell: device id and value
+const CLUSTER_CELL_WIDTH: usize = 10;
+/// Width of the host column: marker, status, name and worst score
+const CLUSTER_HOST_WIDTH: usize = 28;
 
+fn draw_cluster(f: &mut Frame, area: Rect, app: &App) {
     let chunks = Layout::default()
-        .direction(Direction::Horizontal)
+        .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage(60),
-            Constraint::Percentage(40),
+            Constraint::Length(3),  // Summary and colour scale
+            Constraint::Min(3),     // Heatmap
         ])
         .split(area);
+    
+    let hosts = app.cluster_hosts();
+    let gpus = &app.last_sample.gpus;
+    let thresholds = &app.settings_manager.get_settings().health_thresholds;
+    
+    // 🌐 Summary across all hosts
+    let mut counts = [0; 4];
+    for health in gpus.iter().filter_map(|gpu| app.gpu_health.get(&gpu.display_id())) {
+        counts[health.status.clone() as usize] += 1;
+    }
+    // GPUs without a reading are left out rather than counted as 0
+    let utilizations: Vec<f32> = gpus.iter().filter_map(|gpu| gpu.utilization).collect();
+    let average_util = (!utilizations.is_empty())
+        .then(|| utilizations.iter().sum::<f32>() / utilizations.len() as f32);
+    let hottest = gpus.iter().filter_map(|gpu| gpu.temperature).reduce(f32::max);
+    let mut summary = vec![Span::raw(format!(
+        "🌐 Hosts: {} • 🎮 GPUs: {} • ⚡ Avg: {} • 🌡️ Max: {} • 🔴 {} 🟡 {} 🔵 {} 🟢 {}   Scale: ",
+        hosts.len(),
+        gpus.len(),
+        average_util.map_or("N/A".to_string(), |u| format!("{:.0}%", u)),
+        hottest.map_or("N/A".to_string(), |t| format!("{:.0}°C", t)),
+        counts[3], counts[2], counts[1], counts[0]
+    ))];
+    summary.extend((0..=4).map(|step| Span::styled("  ", Style::default().bg(heat_color(step as f32 / 4.0)))));
+    
+    let summary_paragraph = Paragraph::new(Line::from(summary))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" 🗺️  Cluster Overview ")
+            .title_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .style(Style::default().bg(Color::Black)))
+        .style(Style::default().fg(Color::White))
+        .alignment(Alignment::Center);
+    f.render_widget(summary_paragraph, chunks[0]);
+    
+    // 🔥 One line per host, unhealthiest first
+    let grid = cluster_grid_area(area);
+    let visible = (grid.height.saturating_sub(2) as usize).max(1);
+    let selected = app.cluster_selected.min(hosts.len().saturating_sub(1));
+    let mut offset = app.cluster_offset.get().min(selected);
+    if selected >= offset + visible {
+        offset = selected + 1 - visible;
+    }
+    app.cluster_offset.set(offset);
+    
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, host) in hosts.iter().enumerate().skip(offset).take(visible) {
+        let name = app.host_label(&host.host);
+        let status = host.gpus.iter()
+            .filter_map(|&g| app.gpu_health.get(&gpus[g].display_id()))
+            .map(|health| health.status.clone())
+            .max_by_key(|status| status.clone() as usize)
+            .map_or("⚪", |status| status.emoji());
+        let focused = host.host.is_some() && host.host == app.focused_host;
+        let stale = app.host_status(&host.host).filter(|status| status.is_stale());
+        let label = format!(
+            "{} {} {:<17.17} {:>4}",
+            if i == selected { "▶" } else if focused { "🔍" } else { " " },
+            if stale.is_some() { "⏳" } else { status },
+            name,
+            match stale.and_then(|status| status.age()) {
+                Some(age) => utils::format_age(age),
+                None => host.worst_score.map_or("--".to_string(), |score| format!("{:.0}", score)),
+            },
+        );
+        let label_style = if i == selected {
+            Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD)
+        } else if stale.is_some() {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        
+        let mut spans = vec![Span::styled(format!("{:<width$}", label, width = CLUSTER_HOST_WIDTH), label_style)];
+        for &g in &host.gpus {
+            let gpu = &gpus[g];
+            let value = app.cluster_metric.value(gpu, app.gpu_health.get(&gpu.display_id()));
+            let (text, style) = match value {
+                Some(value) => (
+                    format!("{:<4}{:>5}", gpu.local_id(), app.cluster_metric.format(value)),
+                    Style::default().bg(heat_color(app.cluster_metric.heat(value, thresholds))).fg(Color::Black),
+                ),
+                // Too narrow for the reason; red marks a failed reading
+                None => {
+                    let failed = app.cluster_metric.sensor().is_some_and(|sensor| gpu.missing_reason(sensor) == "error");
+                    let bg = if failed { Color::Red } else { Color::DarkGray };
+                    (format!("{:<4}{:>5}", gpu.local_id(), "N/A"), Style::default().bg(bg).fg(Color::White))
+                }
+            };
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(format!("{:<width$}", text, width = CLUSTER_CELL_WIDTH - 1), style));
+        }
+        lines.push(Line::from(spans));
+    }
+    
+    // Hosts that never answered have no GPUs to show
+    for host in app.last_sample.hosts.iter().filter(|host| host.last_seen.is_none()) {
+        let error = host.error.as_deref().unwrap_or("no data");
+        lines.push(Line::from(Span::styled(format!("  ❌ {}: {}", host.name, error), Style::default().fg(Color::Red))));
+    }
+    
+    let title = format!(" {} • m=Metric • Enter=Drill down ", app.cluster_metric.label());
+    let grid_paragraph = Paragraph::new(lines)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Magenta))
+            .title(title)
+            .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
+            .style(Style::default().bg(Color::Black)));
+    f.render_widget(grid_paragraph, grid);
+}
+
+/// The heatmap part of the cluster view in `area`
+fn cluster_grid_area(area: Rect) -> Rect {
+    Rect::new(area.x, area.y + 3, area.width, area.height.saturating_sub(3))
+}
+
+/// Host row of the cluster heatmap at `x`/`y`
+pub fn cluster_row_at(app: &App, area: Rect, x: u16, y: u16) -> Option<usize> {
+    let grid = cluster_grid_area(area);
+    let inner = Rect::new(grid.x + 1, grid.y + 1, grid.width.saturating_sub(2), grid.height.saturating_sub(2));
+    if !contains(inner, x, y) {
+        return None;
+    }
+    let row = (y - inner.y) as usize + app.cluster_offset.get();
+    (row < app.cluster_hosts().len()).then_some(row)
+}
+
+/// Green through yellow to red for a heat of 0.0 to 1.0
+fn heat_color(heat: f32) -> Color {
+    let heat = heat.clamp(0.0, 1.0);
+    let (from, to, t) = if heat < 0.5 {
+        ((40.0, 160.0, 70.0), (220.0, 190.0, 40.0), heat * 2.0)
+    } else {
+        ((220.0, 190.0, 40.0), (210.0, 40.0, 40.0), (heat - 0.5) * 2.0)
+    };
+    let mix = |a: f32, b: f32| (a + (b - a) * t) as u8;
+    Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+fn draw_footer(f: &mut Frame, controls_area: Rect, tabs_area: Rect, app: &App) {
+    let help_text = match app.view_mode {
+        ViewMode::Processes => "q=Quit • ↑↓=Nav • Enter=Details • Del=Signal • /=Search • Ctrl+E=Export • h=Help",
+        ViewMode::Performance => "q=Quit • F1-F4=GPU • Ctrl+E=Export • h=Help",
+        ViewMode::Hardware => "q=Quit • F1-F4=GPU • Ctrl+E=Export • h=Help",
+        ViewMode::Health => "q=Quit • F1-F4=GPU • Ctrl+E=Export • h=Help",
+        ViewMode::Cluster => "q=Quit • ↑↓=Host • Enter=Drill down • m=Metric • Esc=All hosts • h=Help",
+    };
+    
+    // The search prompt and active filter take over the controls pane
+    let (help_text, title, border_color) = if app.search_mode {
+        let hint = match &app.filter_error {
+            Some(error) => format!("  ⚠️ {}", error),
+            None => "  Enter=Apply • Esc=Clear".to_string(),
+        };
+        (format!("/{}▌{}", app.filter_text, hint), " 🔍 Search ".to_string(), Color::Magenta)
+    } else if !app.filter_text.is_empty() {
+        (format!("Filter: {}  • /=Edit • Esc=Clear", app.filter_text), " 🔍 Filter Active ".to_string(), Color::Magenta)
+    } else if let Some(timing) = sample_timing(app) {
+        (help_text.to_string(), timing, Color::Yellow)
+    } else {
+        (help_text.to_string(), " 🎮 Controls ".to_string(), Color::Yellow)
+    };
 
     // 🎮 Controls with bright colors
     let help_paragraph = Paragraph::new(help_text)
         .block(Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Yellow))
-            .title(" 🎮 Controls ")
+            .border_style(Style::default().fg(border_color))
+            .title(title)
             .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
             .style(Style::default().bg(Color::Black)))
         .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
-    f.render_widget(help_paragraph, chunks[0]);
+    f.render_widget(help_paragraph, controls_area);
 
     // 📋 Tabs
-    let tabs = Tabs::new(vec!["🔧 Proc", "📊 Perf", "🖥️ HW", "🏥 Health"])
+    let tabs = Tabs::new(VIEW_TABS.iter().map(|(title, _)| *title).collect())
         .block(Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
@@ -843,55 +1410,95 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
             .title(" 📋 Views ")
             .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
             .style(Style::default().bg(Color::Black)))
-        .select(match app.view_mode {
-            ViewMode::Processes => 0,
-            ViewMode::Performance => 1,
-            ViewMode::Hardware => 2,
-            ViewMode::Health => 3,
-        })
+        .select(VIEW_TABS.iter().position(|(_, mode)| *mode == app.view_mode).unwrap_or(0))
         .style(Style::default().fg(Color::Gray))
         .highlight_style(Style::default()
             .fg(Color::Yellow)
             .bg(Color::Blue)
             .add_modifier(Modifier::BOLD));
-    f.render_widget(tabs, chunks[1]);
+    f.render_widget(tabs, tabs_area);
 }
 
-fn draw_help_modal(f: &mut Frame, _app: &App) {
-    let area = centered_rect(70, 90, f.size());
-    f.render_widget(Clear, area);
+/// Per-collector sampling time, shown with --debug or when sampling can't
+/// keep up with the update interval
+fn sample_timing(app: &App) -> Option<String> {
+    let sample = &app.last_sample;
+    let total = sample.total_time();
+    let slow = total > Duration::from_millis(app.update_interval);
+    if !app.debug_mode && !slow {
+        return None;
+    }
+    
+    let collectors: Vec<String> = sample.timings.iter()
+        .map(|t| format!("{} {}ms", t.name, t.elapsed.as_millis()))
+        .collect();
+    let icon = if slow { "🐢 Sampling slower than interval" } else { "⏱️ Sampling" };
+    Some(format!(" {}: {}ms ({}) ", icon, total.as_millis(), collectors.join(" • ")))
+}
 
-    let help_text = "🚀 GPUTop - Professional GPU Monitoring\n\n\
+const HELP_TEXT: &str = "🚀 GPUTop - Professional GPU Monitoring\n\n\
 🎮 NAVIGATION:\n\
 ↑↓ / k j          Navigate up/down\n\
 PgUp/PgDn         Navigate by page\n\
 Home/End          Go to first/last\n\
 Tab/Shift+Tab     Switch view modes\n\
-F1-F4             Switch GPU (0-3)\n\n\
+F1-F4             Switch GPU (0-3)\n\
+[ / ]             Cycle devices incl. MIG instances\n\n\
 🎯 ACTIONS:\n\
 Enter             Show process details\n\
-Delete / Alt+K    Kill selected process\n\
-Ctrl+P            Open command palette\n\n\
+Delete / Alt+K    Signal selected process (confirm, choose signal)\n\
++/- i >/<         Renice / I/O priority (process details)\n\
+m >/< x           Compute mode / app clocks (Hardware view)\n\
+Ctrl+P            Open command palette\n\
+/                 Search/filter processes (Esc clears)\n\n\
 📁 EXPORT:\n\
 Ctrl+E            Export full snapshot to CSV\n\
-Ctrl+S            Export processes to CSV\n\n\
+Ctrl+S            Export processes to CSV\n\
+Ctrl+R            Export energy & cost report\n\n\
 📊 SORTING (Processes View):\n\
 1                 Sort by PID\n\
 2                 Sort by User\n\
 3                 Sort by GPU Usage\n\
 4                 Sort by Memory\n\
-5                 Sort by Command\n\n\
+5                 Sort by Command\n\
+6                 Sort by Host (with --connect)\n\n\
 🎨 UI CONTROLS:\n\
 t                 Toggle tree view\n\
+←/→ / Space       Collapse/expand subtree (tree view)\n\
+g                 Group processes by container\n\
 c                 Collapse/expand panes\n\
++ / -             Slow down / speed up updates\n\
 h                 Toggle this help\n\
 Alt+S             Open settings panel\n\n\
 🚪 GENERAL:\n\
 q / Ctrl+C        Quit application\n\
 Esc               Close modals\n\n\
-Press 'h' or ESC to close this help";
+🖱️ MOUSE:\n\
+Click             Select row / switch tab / sort by header\n\
+Double-click      Show process details\n\
+Wheel             Scroll process table or this help\n\n\
+🌐 CLUSTER VIEW:\n\
+↑↓ / k j          Select host (unhealthiest first)\n\
+m                 Color by utilization, temperature or health\n\
+Enter             Drill down to the host's views\n\
+Esc               Show all hosts again\n\n\
+🔍 SEARCH SYNTAX:\n\
+python            Match command, user, PID or container\n\
+user:alice        Match a field (user, cmd, pid, container, host)\n\
+gpu:1 / gpu:0:2   Processes on a GPU or MIG instance\n\
+mem>2G usage>=50  Compare memory or GPU usage\n\
+re:^py /^py/      Regular expression\n\n\
+Press 'h' or ESC to close this help • ↑↓/wheel to scroll";
+
+pub fn help_line_count() -> usize {
+    HELP_TEXT.lines().count()
+}
 
-    let help_paragraph = Paragraph::new(help_text)
+fn draw_help_modal(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 90, f.size());
+    f.render_widget(Clear, area);
+
+    let help_paragraph = Paragraph::new(HELP_TEXT)
         .block(Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
@@ -900,7 +1507,8 @@ Press 'h' or ESC to close this help";
             .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
             .style(Style::default().bg(Color::Black)))
         .style(Style::default().fg(Color::White))
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: true })
+        .scroll((app.help_scroll.min(help_line_count().saturating_sub(1) as u16), 0));
     f.render_widget(help_paragraph, area);
 }
 
@@ -909,47 +1517,66 @@ fn draw_settings_modal(f: &mut Frame, app: &App) {
     f.render_widget(Clear, area);
 
     let settings = app.settings_manager.get_settings();
+    let mut lines = vec![Line::from(Span::styled(
+        "⚙️  GPUTop Settings Configuration",
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    ))];
+    let mut section = "";
     
-    let settings_text = format!(
-        "⚙️  GPUTop Settings Configuration\n\n\
-📊 HEALTH THRESHOLDS:\n\
-Temperature Warning:     {:.1}°C\n\
-Temperature Critical:    {:.1}°C\n\
-Power Warning:           {:.1}%\n\
-Power Critical:          {:.1}%\n\
-Memory Warning:          {:.1}%\n\
-Memory Critical:         {:.1}%\n\
-Low Utilization:         {:.1}%\n\
-High Utilization:        {:.1}%\n\n\
-🔔 NOTIFICATIONS:\n\
-Enabled:                 {}\n\
-Min Interval:            {}s\n\
-Export Notifications:    {}\n\
-Process Notifications:   {}\n\n\
-⏱️  PERFORMANCE:\n\
-Update Interval:         {}ms\n\
-Max History Points:      {}\n\n\
-💾 ACTIONS:\n\
-s = Save Settings    r = Reset to Defaults    Esc = Close\n\
-\n\
-Settings file: ~/.config/gputop/settings.json",
-        settings.health_thresholds.temperature_warning,
-        settings.health_thresholds.temperature_critical,
-        settings.health_thresholds.power_warning,
-        settings.health_thresholds.power_critical,
-        settings.health_thresholds.memory_usage_warning,
-        settings.health_thresholds.memory_usage_critical,
-        settings.health_thresholds.utilization_low,
-        settings.health_thresholds.utilization_high,
-        if settings.notification_settings.enabled { "Yes" } else { "No" },
-        settings.notification_settings.min_interval_seconds,
-        if settings.notification_settings.show_export_notifications { "Yes" } else { "No" },
-        if settings.notification_settings.show_process_notifications { "Yes" } else { "No" },
-        settings.update_interval_ms,
-        settings.max_history_points
-    );
+    for (i, field) in SettingsField::ALL.iter().enumerate() {
+        if field.section() != section {
+            section = field.section();
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(section, Style::default().fg(Color::Yellow))));
+        }
+        
+        let selected = i == app.settings_selected;
+        let value = match &app.settings_edit {
+            Some(buffer) if selected => format!("{}▌", buffer),
+            _ => format!("{}{}", field.value(settings), field.unit()),
+        };
+        let style = if selected {
+            Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        
+        lines.push(Line::from(Span::styled(
+            format!("{} {:<24} {}", if selected { "▶" } else { " " }, format!("{}:", field.label()), value),
+            style,
+        )));
+    }
+    
+    lines.push(Line::from(""));
+    if let Some(error) = &app.settings_error {
+        lines.push(Line::from(Span::styled(
+            format!("⚠️  {}", error),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+    } else if app.settings_dirty() {
+        lines.push(Line::from(Span::styled(
+            "● Unsaved changes (previewing live) - press s to save",
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    
+    lines.push(Line::from(Span::styled("💾 ACTIONS:", Style::default().fg(Color::Yellow))));
+    lines.push(Line::from(if app.settings_edit.is_some() {
+        "Enter = Apply    Esc = Cancel edit"
+    } else {
+        "↑↓ = Select    Enter = Edit/Toggle    s = Save    r = Reset to Defaults    Esc = Close"
+    }));
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!("Settings file: {}", app.settings_manager.config_path().display())));
+    let layers: Vec<String> = app.settings_manager.layers().iter().map(|p| p.display().to_string()).collect();
+    if layers.len() > 1 {
+        lines.push(Line::from(format!("Merged from:   {}", layers.join(" → "))));
+    }
+    if let Some(profile) = app.settings_manager.profile() {
+        lines.push(Line::from(format!("Profile:       {}", profile)));
+    }
 
-    let settings_paragraph = Paragraph::new(settings_text)
+    let settings_paragraph = Paragraph::new(lines)
         .block(Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
@@ -958,7 +1585,7 @@ Settings file: ~/.config/gputop/settings.json",
             .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
             .style(Style::default().bg(Color::Black)))
         .style(Style::default().fg(Color::White))
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: false });
     f.render_widget(settings_paragraph, area);
 }
 
@@ -979,77 +1606,179 @@ fn draw_command_palette(f: &mut Frame, app: &App) {
     f.render_widget(input_paragraph, area);
 }
 
+fn draw_signal_dialog(f: &mut Frame, app: &App) {
+    let Some(request) = &app.signal_dialog else { return };
+    let area = centered_rect(60, 60, f.size());
+    f.render_widget(Clear, area);
+    
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("🎯 {}", request.command),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("PID: {}    User: {}", request.pid, request.user)),
+        Line::from(format!(
+            "GPU Memory: {}{}",
+            utils::format_bytes(request.memory_usage),
+            request.device.as_ref().map(|d| format!(" on {}", d)).unwrap_or_default()
+        )),
+        Line::from(""),
+        Line::from(Span::styled("📨 SIGNAL:", Style::default().fg(Color::Yellow))),
+    ];
+    
+    for (i, signal) in ProcessSignal::ALL.iter().enumerate() {
+        let selected = i == request.signal;
+        let style = if selected {
+            Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{} {}. {:<8} {}", if selected { "▶" } else { " " }, i + 1, signal.name(), signal.description()),
+            style,
+        )));
+    }
+    
+    lines.push(Line::from(""));
+    if ProcessSignal::ALL[request.signal] == ProcessSignal::Term {
+        lines.push(Line::from(format!(
+            "⏱️  Escalate to SIGKILL if still running after timeout: {}  (e to toggle)",
+            if request.escalate { "Yes" } else { "No" }
+        )));
+    }
+    lines.push(Line::from("Enter/y = Send    ↑↓/1-5 = Choose signal    Esc/n = Cancel"));
+    
+    draw_confirm_dialog(f, area, " ⚠️  Signal Process ", lines);
+}
+
+fn draw_device_control_dialog(f: &mut Frame, app: &App) {
+    let Some(request) = &app.device_control_dialog else { return };
+    let area = centered_rect(60, 30, f.size());
+    f.render_widget(Clear, area);
+    
+    let lines = vec![
+        Line::from(Span::styled(
+            format!("🖥️  GPU {}: {}", request.gpu_index, request.gpu_name),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("Current: {}", request.current)),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("⚙️  {}", request.control.description()),
+            Style::default().fg(Color::Yellow),
+        )),
+        Line::from("This affects every process on the GPU."),
+        Line::from(""),
+        Line::from("Enter/y = Apply    Esc/n = Cancel"),
+    ];
+    
+    draw_confirm_dialog(f, area, " ⚠️  Change GPU Setting ", lines);
+}
+
+/// Frame shared by the dialogs that confirm a disruptive action
+fn draw_confirm_dialog(f: &mut Frame, area: Rect, title: &str, lines: Vec<Line>) {
+    let dialog = Paragraph::new(lines)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Red))
+            .title(title)
+            .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+            .style(Style::default().bg(Color::Black)))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false });
+    f.render_widget(dialog, area);
+}
+
 fn draw_process_details_modal(f: &mut Frame, app: &App) {
-    if let Some(selected_idx) = app.selected_process {
-        if let Some(process) = app.processes.get(selected_idx) {
-            let area = centered_rect(80, 70, f.size());
-            f.render_widget(Clear, area);
-
-            let memory_mb = process.memory_usage / (1024 * 1024);
-            let memory_gb = memory_mb as f64 / 1024.0;
-            
-            let gpu_memory_pct = if let Some(gpu) = app.gpus.get(app.current_gpu) {
-                (process.memory_usage as f64 / gpu.memory_total as f64) * 100.0
-            } else {
-                0.0
-            };
+    if let Some(process) = app.selected_process() {
+        let area = centered_rect(80, 70, f.size());
+        f.render_widget(Clear, area);
 
-            let container_info = if let Some(container) = &process.container_id {
-                format!("🐳 Container: {}", container)
-            } else {
-                "📦 Native Process".to_string()
+        let memory_mb = process.memory_usage / (1024 * 1024);
+        let memory_gb = memory_mb as f64 / 1024.0;
+        
+        let gpu_memory_pct = app.gpus.get(app.current_gpu)
+            .and_then(|gpu| gpu.memory_total)
+            .filter(|total| *total > 0)
+            .map_or(0.0, |total| (process.memory_usage as f64 / total as f64) * 100.0);
+
+        let container_info = if let Some(container) = &process.container {
+            let pod = match &container.pod {
+                Some(pod) => format!(
+                    " • ☸️  Pod: {}/{}",
+                    pod.namespace.as_deref().unwrap_or("?"),
+                    pod.name.as_deref().unwrap_or(&pod.uid)
+                ),
+                None => String::new(),
             };
+            format!("{} Container: {} ({}){}", container.runtime.emoji(), container.short_id(), container.runtime.text(), pod)
+        } else {
+            "📦 Native Process".to_string()
+        };
 
-            let parent_info = if let Some(parent) = process.parent_pid {
-                format!("👨‍👩‍👧‍👦 Parent PID: {}", parent)
-            } else {
-                "🌱 Root Process".to_string()
-            };
+        let device_info = match (process.gpu_index, process.gpu_instance_id, process.compute_instance_id) {
+            (Some(gpu), Some(gi), Some(ci)) => format!("🖥️  Device: GPU {} • MIG GI {} / CI {}", gpu, gi, ci),
+            (Some(gpu), _, _) => format!("🖥️  Device: GPU {}", gpu),
+            _ => "🖥️  Device: Unknown".to_string(),
+        };
 
-            let details_text = format!(
-                "📋 PROCESS DETAILS\n\n\
-                🔧 Command: {}\n\
-                🆔 PID: {}\n\
-                👤 User: {}\n\
-                {}\n\
-                {}\n\n\
-                📊 RESOURCE USAGE:\n\
-                ⚡ GPU Usage: {:.1}%\n\
-                🧠 Memory Usage: {:.1}% ({:.1} GB / {} MB)\n\
-                🎥 Encoder Usage: {:.1}%\n\
-                📺 Decoder Usage: {:.1}%\n\n\
-                🔧 TECHNICAL INFO:\n\
-                🎯 Priority: {}\n\
-                📈 Context ID: {}\n\n\
-                Press ESC or Enter to close",
-                process.command,
-                process.pid,
-                process.user,
-                container_info,
-                parent_info,
-                process.gpu_usage,
-                gpu_memory_pct,
-                memory_gb,
-                memory_mb,
-                process.encoder_usage,
-                process.decoder_usage,
-                process.priority,
-                process.context_id.map_or("N/A".to_string(), |id| id.to_string())
-            );
-
-            let details_paragraph = Paragraph::new(details_text)
-                .block(Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(Color::Cyan))
-                    .title(" 🔍 Process Information ")
-                    .title_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
-                    .style(Style::default().bg(Color::Black)))
-                .style(Style::default().fg(Color::White))
-                .alignment(Alignment::Left)
-                .wrap(Wrap { trim: true });
-            f.render_widget(details_paragraph, area);
-        }
+        let parent_info = if let Some(parent) = process.parent_pid {
+            format!("👨‍👩‍👧‍👦 Parent PID: {}", parent)
+        } else {
+            "🌱 Root Process".to_string()
+        };
+
+        let details_text = format!(
+            "📋 PROCESS DETAILS\n\n\
+            🔧 Command: {}\n\
+            🆔 PID: {}\n\
+            👤 User: {}\n\
+            {}\n\
+            {}\n\
+            {}\n\n\
+            📊 RESOURCE USAGE:\n\
+            ⚡ GPU Usage: {}\n\
+            🧠 Memory Usage: {:.1}% ({:.1} GB / {} MB)\n\
+            🎥 Encoder Usage: {}\n\
+            📺 Decoder Usage: {}\n\
+            🔋 Energy (session): {}\n\n\
+            🔧 TECHNICAL INFO:\n\
+            🎯 Priority (nice): {}\n\
+            💽 I/O Priority: {}\n\
+            📈 Context ID: {}\n\n\
+            +/- = Raise/lower priority • i = Cycle I/O class • >/< = Raise/lower I/O level\n\
+            Press ESC or Enter to close",
+            process.command,
+            process.pid,
+            process.user,
+            container_info,
+            parent_info,
+            device_info,
+            process.gpu_usage.map_or("N/A".to_string(), |u| format!("{:.1}%", u)),
+            gpu_memory_pct,
+            memory_gb,
+            memory_mb,
+            process.encoder_usage.map_or("N/A".to_string(), |u| format!("{:.1}%", u)),
+            process.decoder_usage.map_or("N/A".to_string(), |u| format!("{:.1}%", u)),
+            app.energy_tracker.process_energy_wh(process.pid).map_or("N/A".to_string(), |e| format!("{:.3} Wh", e)),
+            process.priority,
+            process.io_priority.map_or("N/A".to_string(), |io| io.text()),
+            process.context_id.map_or("N/A".to_string(), |id| id.to_string())
+        );
+
+        let details_paragraph = Paragraph::new(details_text)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(" 🔍 Process Information ")
+                .title_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                .style(Style::default().bg(Color::Black)))
+            .style(Style::default().fg(Color::White))
+            .alignment(Alignment::Left)
+            .wrap(Wrap { trim: true });
+        f.render_widget(details_paragraph, area);
     }
 }
 
@@ -1070,6 +1799,53 @@ fn draw_status_message(f: &mut Frame, area: Rect, app: &App) {
 }
 
 
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// A metric's formatted value, or "N/A (unsupported)" / "N/A (error)"
+fn metric<T>(gpu: &crate::gpu::GpuInfo, sensor: &str, value: Option<T>, format: impl FnOnce(T) -> String) -> String {
+    match value {
+        Some(value) => format(value),
+        None => format!("N/A ({})", gpu.missing_reason(sensor)),
+    }
+}
+
+/// Each fan's speed with its target and control policy, e.g.
+/// "#0 45% → 60% auto • #1 44% → 60% auto"
+fn fans_text(gpu: &crate::gpu::GpuInfo) -> String {
+    if gpu.fans.is_empty() {
+        return metric(gpu, "fan_speed", gpu.fan_speed, |f| format!("{:.0}%", f));
+    }
+    gpu.fans.iter()
+        .map(|fan| {
+            let mut text = format!("#{} {}", fan.index, metric(gpu, "fan_speed", fan.speed, |s| format!("{:.0}%", s)));
+            if let Some(target) = fan.target_speed {
+                text.push_str(&format!(" → {:.0}%", target));
+            }
+            if let Some(policy) = fan.policy {
+                text.push_str(&format!(" {}", policy.text()));
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join(" • ")
+}
+
+/// "used/total GB (percent)"
+fn memory_text(gpu: &crate::gpu::GpuInfo) -> String {
+    let memory = gpu.memory_used.zip(gpu.memory_total);
+    metric(gpu, "memory", memory, |(used, total)| {
+        format!("{:.1}GB/{:.1}GB ({:.1}%)", used as f64 / GB, total as f64 / GB, gpu.memory_percent().unwrap_or(0.0))
+    })
+}
+
+fn format_power_with_limit(gpu: &crate::gpu::GpuInfo) -> String {
+    match (gpu.power_draw, &gpu.power_limits) {
+        (Some(draw), Some(limits)) => format!("{:.0}/{:.0}W", draw, limits.enforced),
+        (Some(draw), None) => format!("{:.0}W", draw),
+        (None, _) => format!("N/A ({})", gpu.missing_reason("power_draw")),
+    }
+}
+
 // Helper functions for color coding
 fn get_usage_color(usage: f32) -> Color {
     if usage > 80.0 {
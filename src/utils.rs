@@ -36,6 +36,16 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// Compact age such as `45s`, `12m` or `3h`
+pub fn format_age(age: chrono::Duration) -> String {
+    let seconds = age.num_seconds().max(0);
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m", seconds / 60),
+        _ => format!("{}h", seconds / 3600),
+    }
+}
+
 pub fn truncate_string(s: &str, max_width: usize) -> String {
     if s.width() <= max_width {
         s.to_string()